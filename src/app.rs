@@ -1,10 +1,26 @@
+use chrono::Local;
 use std::error::Error;
+use regex::Regex;
+use std::fs;
+use std::path::PathBuf;
 use ratatui::widgets::ListState;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use winapi::shared::windef::HWND;
+use aim2go::config::{update_crosshair, CrosshairConfig, LayerConfig};
+use aim2go::crosshair::{decode_share_code, encode_share_code};
+use aim2go::window::{WindowBackend, WindowHandle};
 
-use crate::thread::{ThreadHandler, ThreadType};
-use crate::config::load_game_config;
+use crate::command::{Command, CommandSpec, COMMANDS};
+use crate::log::{logs_dir, LogBuffer, LogEntry, LogLevel, LogView, SessionLog};
+use crate::editor::{ConfigEditor, Status};
+use crate::event::{AppEvent, AppEventSender, OverlayStats};
+use crate::overlay_process::OverlayCommand;
+use crate::prompt::Prompt;
+use crate::keymap::Keymap;
+use crate::thread::{StopOutcome, ThreadHandler, ThreadType, WorkerState, WorkerStatus};
+use crate::config::EffectiveConfig;
+use crate::config::watch::{ConfigReload, ConfigWatcher};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn Error>>;
@@ -19,9 +35,11 @@ pub struct App {
     /// Game name
     pub game: String,
     /// Game window
-    pub game_window: HWND,
-    /// TUI Theme
-    pub theme: String,
+    pub game_window: WindowHandle,
+    /// Current config snapshot, shared with the render path
+    pub config: Arc<EffectiveConfig>,
+    /// TUI key bindings
+    pub keymap: Keymap,
     /// Logo gradient position
     pub logo_gradient: usize,
     /// Logo
@@ -31,15 +49,35 @@ pub struct App {
     /// Menu items
     pub menu_items: Vec<String>,
     /// Execute log
-    pub log: Vec<String>,
+    pub log: LogBuffer,
+    /// Scroll position and filters of the execute log
+    pub log_view: LogView,
+    /// File the log of this session is written to, if it could be created
+    pub session_log: Option<SessionLog>,
+    /// Execute command prompt
+    pub prompt: Prompt,
     /// Thread handler
     pub thread_handler: ThreadHandler,
+    /// Last reported state of each worker
+    pub worker_states: HashMap<ThreadType, WorkerState>,
+    /// Latest rendering statistics of the running overlay
+    pub overlay_stats: Option<OverlayStats>,
     /// Is game overlay active?
     pub is_overlay_active: bool,
     /// Was the game overlay previously active?
     pub overlay_was_active: bool,
     /// Last overlay check
     pub last_overlay_check: Instant,
+    /// Watches the config files for edits
+    pub config_watcher: ConfigWatcher,
+    /// Config mode form
+    pub config_editor: ConfigEditor,
+    /// Was the last quit held back because the config form has unsaved changes?
+    pub quit_warned: bool,
+    /// Crosshair of the config form last sent to the overlay
+    previewed_crosshair: Option<CrosshairConfig>,
+    /// Layers of the config form last sent to the overlay
+    previewed_layers: Option<Vec<LayerConfig>>,
 }
 
 #[derive(Debug)]
@@ -59,23 +97,18 @@ pub enum Mode {
 
 impl App {
     /// Constructs a new instance of [`App`].
-    pub fn new(game: &str, game_window: HWND) -> Self {
+    pub fn new(
+        game: &str,
+        game_window: WindowHandle,
+        backend: Arc<dyn WindowBackend>,
+        config: &EffectiveConfig,
+        events: AppEventSender,
+    ) -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));  // Start with the first item selected
 
-        // Load the config for the given game
-        let config = match load_game_config(game) {
-            Ok(cfg) => cfg,
-            Err(_) => {
-                eprintln!("Failed to load config for game '{}'", game);
-                panic!("Could not load configuration.");
-            }
-        };
-
         let logo = config.app.ascii_art.clone();
 
-        let theme = config.app.theme.clone();
-
         // Feature-based menu item selection
         #[cfg(feature = "free")]
         let menu_items = Self::get_free_menu_items();
@@ -85,25 +118,54 @@ impl App {
         // Ensure gradient calculation doesn't panic if logo is empty
         let max_logo_length = logo.lines().map(|line| line.len()).max().unwrap_or(1);
 
-        Self {
+        let reloads = events.clone();
+        let config_watcher = ConfigWatcher::spawn(game, config.clone(), move |reload| {
+            reloads.send(AppEvent::ConfigChanged(reload));
+        });
+        let snapshot = Arc::clone(&config_watcher.subscribe().borrow());
+
+        let mut app = Self {
             running: true,
             game: game.to_string(),
-            game_window: game_window,
+            game_window,
             logo_gradient: max_logo_length,
             logo: logo.to_string(),
             menu_state: list_state,
             menu_items,             mode: Mode::Menu,
-            log: Vec::new(),
-            thread_handler: ThreadHandler::new(),
+            log: LogBuffer::default(),
+            log_view: LogView::default(),
+            session_log: None,
+            prompt: Prompt::default(),
+            thread_handler: ThreadHandler::new(game, config_watcher.subscribe(), backend, events),
+            worker_states: HashMap::new(),
+            overlay_stats: None,
             is_overlay_active: true,
             overlay_was_active: false,
             last_overlay_check: Instant::now(),
-            theme,
+            config: snapshot,
+            keymap: Keymap::new(&config.app.tui_keys),
+            config_watcher,
+            config_editor: ConfigEditor::new(game, &config.game),
+            quit_warned: false,
+            previewed_crosshair: None,
+            previewed_layers: None,
+        };
+
+        match SessionLog::create(game, &config.app.session_log) {
+            Ok(session_log) => app.session_log = Some(session_log),
+            Err(e) => app.log_message(LogLevel::Warn, "LOG", format!("Could not create the session log: {}", e)),
         }
+        app
+    }
+
+    /// Starts the workers that run for the whole session.
+    pub async fn start_workers(&mut self) {
+        self.thread_handler.start_thread(self.game_window, ThreadType::WindowWatch).await;
     }
 
     /// Handles the tick event of the terminal asynchronously.
     pub async fn tick(&mut self) {
+
         match &self.mode {
             Mode::Menu => {        
                 self.increment_gradient();
@@ -116,11 +178,88 @@ impl App {
                 }
             }
             Mode::Quit => {
-                self.quit();
+                self.mode = Mode::Menu;
+                self.request_quit().await;
             }
             _ => {}
         }
     }
+    /// Handles an event published by a background task.
+    pub async fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::WorkerStatus(status) => self.apply_worker_status(status),
+            AppEvent::Log(entry) => self.push_log(entry),
+            AppEvent::ConfigChanged(ConfigReload::Applied(config, warnings)) => {
+                self.apply_config(config);
+                self.log_message(LogLevel::Info, "CONFIG", "Config reloaded.");
+                for warning in &warnings {
+                    self.log_message(LogLevel::Warn, "CONFIG", warning.summary());
+                }
+            }
+            AppEvent::ConfigChanged(ConfigReload::Rejected(e)) => {
+                self.log_message(
+                    LogLevel::Error,
+                    "CONFIG",
+                    format!("Rejected invalid config, keeping the last good one: {}", e.summary()),
+                );
+            }
+            AppEvent::WindowLost(window) => {
+                self.log_message(
+                    LogLevel::Error,
+                    "WINDOW",
+                    format!("The game window {:#x} was closed; stopping the overlay.", window.0),
+                );
+                self.is_overlay_active = false;
+                self.check_overlay_status().await;
+            }
+            AppEvent::OverlayStats(stats) => self.overlay_stats = Some(stats),
+        }
+    }
+
+    fn apply_worker_status(&mut self, status: WorkerStatus) {
+        let WorkerStatus { thread_type, state, restart_in } = status;
+        let level = match &state {
+            WorkerState::Starting => LogLevel::Debug,
+            WorkerState::Running | WorkerState::Exited => LogLevel::Info,
+            WorkerState::Failed(_) | WorkerState::Panicked(_) => LogLevel::Error,
+        };
+        let message = match (state.is_stopped(), restart_in) {
+            (true, Some(delay)) => format!("{} {}; restarting in {:.1}s.", thread_type, state, delay.as_secs_f32()),
+            (true, None) => format!("{} {}; not restarting.", thread_type, state),
+            (false, _) => format!("{} {}.", thread_type, state),
+        };
+        self.log_message(level, "WORKER", message);
+
+        // A worker that won't come back is off until it is started again by hand.
+        if state.is_stopped() && restart_in.is_none() && thread_type == ThreadType::GameOverlay {
+            self.is_overlay_active = false;
+            self.overlay_was_active = false;
+        }
+        if state.is_stopped() && thread_type == ThreadType::GameOverlay {
+            self.overlay_stats = None;
+            // A new overlay process starts from the config, without the previews.
+            self.previewed_crosshair = None;
+            self.previewed_layers = None;
+        }
+        self.worker_states.insert(thread_type, state);
+    }
+
+    /// Takes over the app settings of a new config snapshot.
+    fn apply_config(&mut self, config: Arc<EffectiveConfig>) {
+        self.keymap = Keymap::new(&config.app.tui_keys);
+        self.logo = config.app.ascii_art.clone();
+
+        let max_logo_length = self.logo.lines().map(|line| line.len()).max().unwrap_or(1);
+        self.logo_gradient = self.logo_gradient.min(max_logo_length);
+
+        // Don't throw away edits the user hasn't saved yet.
+        if !self.config_editor.dirty {
+            self.config_editor.load(&config.game);
+        }
+
+        self.config = config;
+    }
+
     /// Increments the gradient and resets after a delay for the shimmer effect
     fn increment_gradient(&mut self) {
         let max_length = self.logo.lines().map(|line| line.len()).max().unwrap_or(1);        
@@ -151,6 +290,27 @@ impl App {
         self.running = false;
     }
 
+    /// Quits like [`App::shutdown`], but the first request while the config form has
+    /// unsaved changes only warns about them; asking again discards them and quits.
+    pub async fn request_quit(&mut self) {
+        if self.config_editor.dirty && !self.quit_warned {
+            self.quit_warned = true;
+            let warning = "The config has unsaved changes. Quit again to discard them.";
+            self.config_editor.status = Some(Status::Error(warning.to_string()));
+            self.log_message(LogLevel::Warn, "CONFIG", warning);
+            return;
+        }
+        self.shutdown().await;
+    }
+
+    /// Stops the game overlay and the other workers, then quits the application.
+    pub async fn shutdown(&mut self) {
+        self.is_overlay_active = false;
+        self.check_overlay_status().await;
+        self.thread_handler.stop_thread(&ThreadType::WindowWatch).await;
+        self.quit();
+    }
+
 
     #[cfg(feature = "free")]
     fn get_free_menu_items() -> Vec<String> {
@@ -197,8 +357,226 @@ impl App {
     }
 
     /// Logs a message to the execution log
-    pub fn log_message(&mut self, message: &str) {
-        self.log.push(message.to_string());
+    pub fn log_message(&mut self, level: LogLevel, source: &str, message: impl Into<String>) {
+        self.push_log(LogEntry::new(level, source, message));
+    }
+
+    /// Adds an entry to the execution log and the session log file.
+    fn push_log(&mut self, entry: LogEntry) {
+        if let Some(session_log) = &mut self.session_log {
+            if let Err(e) = session_log.write(&entry) {
+                // Stop writing rather than reporting the same failure for every entry.
+                let path = session_log.path().display().to_string();
+                self.session_log = None;
+                self.log_message(LogLevel::Error, "LOG", format!("Could not write to {}, stopped: {}", path, e));
+            }
+        }
+        self.log_view.entry_added(&entry);
+        self.log.push(entry);
+    }
+
+    /// Runs a line typed into the Execute prompt, logging the outcome.
+    pub async fn run_command(&mut self, line: &str) {
+        self.log_message(LogLevel::Info, "PROMPT", format!("> {}", line));
+
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(e) => {
+                self.log_message(LogLevel::Error, "COMMAND", e);
+                return;
+            }
+        };
+
+        match command {
+            Command::Help(None) => {
+                for spec in &COMMANDS {
+                    self.log_message(LogLevel::Info, "COMMAND", format!("{:<32} {}", spec.synopsis(), spec.summary));
+                }
+            }
+            Command::Help(Some(topic)) => match CommandSpec::find(&topic) {
+                Some(spec) => {
+                    self.log_message(LogLevel::Info, "COMMAND", format!("{}: {}", spec.synopsis(), spec.summary))
+                }
+                None => self.log_message(LogLevel::Error, "COMMAND", format!("No command named `/{}`.", topic)),
+            },
+            Command::Overlay(active) => {
+                if active == self.overlay_was_active {
+                    let state = if active { "running" } else { "stopped" };
+                    self.log_message(LogLevel::Warn, "COMMAND", format!("The overlay is already {}.", state));
+                } else {
+                    self.is_overlay_active = active;
+                    self.check_overlay_status().await;
+                }
+            }
+            Command::OverlayVisible(visible) => {
+                if self.send_overlay(OverlayCommand::SetVisible(visible)) {
+                    let state = if visible { "shown" } else { "hidden" };
+                    self.log_message(LogLevel::Info, "COMMAND", format!("Overlay {}.", state));
+                }
+            }
+            Command::OverlayReset => {
+                let config = Box::new((*self.config).clone());
+                if self.send_overlay(OverlayCommand::Configure(config)) {
+                    self.previewed_crosshair = None;
+                    self.previewed_layers = None;
+                    self.log_message(LogLevel::Info, "COMMAND", "Overlay reset to the config.");
+                }
+            }
+            Command::Crosshair(crosshair_type) => {
+                let message = format!("Previewing a {:?} crosshair.", crosshair_type);
+                let crosshair = CrosshairConfig {
+                    crosshair_type,
+                    ..self.config.game.overlay.crosshair.clone()
+                };
+                if let Err(e) = crosshair.validate() {
+                    self.log_message(LogLevel::Error, "COMMAND", format!("Cannot preview that crosshair: {}.", e));
+                } else if self.send_overlay(OverlayCommand::SetCrosshair(crosshair)) {
+                    self.log_message(LogLevel::Info, "COMMAND", message);
+                }
+            }
+            Command::CrosshairExport => {
+                let code = encode_share_code(&self.config.game.overlay.crosshair);
+                self.log_message(LogLevel::Info, "COMMAND", format!("Share code: {}", code));
+            }
+            Command::CrosshairImport(code) => {
+                let imported = decode_share_code(&code)
+                    .map_err(|e| e.to_string())
+                    .and_then(|crosshair| {
+                        let crosshair_type = crosshair.crosshair_type.clone();
+                        update_crosshair(&self.game, crosshair).map_err(|e| e.summary())?;
+                        Ok(crosshair_type)
+                    });
+                match imported {
+                    Ok(crosshair_type) => {
+                        self.config_watcher.reload();
+                        let message = format!("Imported a {:?} crosshair into config.yaml.", crosshair_type);
+                        self.log_message(LogLevel::Info, "COMMAND", message);
+                    }
+                    Err(e) => self.log_message(LogLevel::Error, "COMMAND", format!("Could not import: {}", e)),
+                }
+            }
+            Command::Color(color) => {
+                if self.send_overlay(OverlayCommand::SetColor(color)) {
+                    self.log_message(LogLevel::Info, "COMMAND", format!("Previewing the colour {}.", color));
+                }
+            }
+            Command::Layers(layers) => {
+                // Layers keep their configured z-order, opacity and anchor.
+                let mut configs: Vec<LayerConfig> = Vec::new();
+                for layer in layers {
+                    if configs.iter().all(|config| config.layer != layer) {
+                        let configured = self.config.game.overlay.layers.iter().find(|config| config.layer == layer);
+                        configs.push(configured.cloned().unwrap_or_else(|| layer.default_config()));
+                    }
+                }
+                let message = if configs.is_empty() {
+                    "Previewing the overlay without layers.".to_string()
+                } else {
+                    let names: Vec<&str> = configs.iter().map(|config| config.layer.name()).collect();
+                    format!("Previewing the layers {}.", names.join(", "))
+                };
+                if self.send_overlay(OverlayCommand::SetLayers(configs)) {
+                    self.log_message(LogLevel::Info, "COMMAND", message);
+                }
+            }
+            Command::Reload => {
+                self.config_watcher.reload();
+                self.log_message(LogLevel::Info, "CONFIG", "Reloading config...");
+            }
+            Command::Status => {
+                let overlay = self.worker_description(&ThreadType::GameOverlay);
+                let status = [
+                    format!("Game: {}", self.game),
+                    format!("Window: {:#x}", self.game_window.0),
+                    format!("Overlay: {}", overlay),
+                    format!("Tick rate: {} ms", self.config.app.tick_rate),
+                    format!("Theme: {}", self.config.app.theme),
+                ];
+                for line in status {
+                    self.log_message(LogLevel::Info, "STATUS", line);
+                }
+            }
+            Command::Clear => {
+                self.log.clear();
+                self.log_view.scroll_back = 0;
+            }
+            Command::SaveLog(path) => match self.save_log(path) {
+                Ok(path) => {
+                    self.log_message(LogLevel::Info, "COMMAND", format!("Saved the log to {}.", path.display()))
+                }
+                Err(e) => self.log_message(LogLevel::Error, "COMMAND", format!("Could not save the log: {}", e)),
+            },
+            Command::Filter(levels) => {
+                self.log_view.levels = if levels.is_empty() { LogLevel::ALL.to_vec() } else { levels };
+                self.log_view.scroll_back = 0;
+            }
+            Command::Search(None) => {
+                self.log_view.search = None;
+                self.log_view.scroll_back = 0;
+            }
+            Command::Search(Some(pattern)) => match Regex::new(&pattern) {
+                Ok(search) => {
+                    self.log_view.search = Some(search);
+                    self.log_view.scroll_back = 0;
+                }
+                Err(e) => self.log_message(LogLevel::Error, "COMMAND", format!("Invalid search pattern: {}", e)),
+            },
+        }
+    }
+
+    /// Sends a command to the running overlay, or logs that it is not running.
+    fn send_overlay(&mut self, command: OverlayCommand) -> bool {
+        if !self.overlay_was_active {
+            self.log_message(LogLevel::Error, "COMMAND", "The overlay is not running; start it with /overlay on.");
+            return false;
+        }
+        self.thread_handler.overlay_control().send(command);
+        true
+    }
+
+    /// Sends the crosshair and render layers being edited in Config mode to the overlay,
+    /// so edits show before they are saved.
+    pub fn preview_config_edits(&mut self) {
+        if !self.overlay_was_active {
+            return;
+        }
+        let editor = &self.config_editor;
+        let control = self.thread_handler.overlay_control();
+
+        if self.previewed_crosshair.as_ref() != Some(&editor.crosshair) {
+            control.send(OverlayCommand::SetCrosshair(editor.crosshair.clone()));
+            self.previewed_crosshair = Some(editor.crosshair.clone());
+        }
+        if self.previewed_layers.as_ref() != Some(&editor.layers) {
+            control.send(OverlayCommand::SetLayers(editor.layers.clone()));
+            self.previewed_layers = Some(editor.layers.clone());
+        }
+    }
+
+    /// Writes the execution log to `path`, or to a timestamped file in the game's `logs/`.
+    fn save_log(&self, path: Option<PathBuf>) -> std::io::Result<PathBuf> {
+        let path = path.unwrap_or_else(|| {
+            let file_name = format!("execute-{}.log", Local::now().format("%Y%m%d-%H%M%S"));
+            logs_dir(&self.game).join(file_name)
+        });
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content: String = self.log.iter().map(|entry| format!("{}\n", entry)).collect();
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Describes a worker's state for the Execute screen and `/status`.
+    pub fn worker_description(&self, thread_type: &ThreadType) -> String {
+        match (self.worker_states.get(thread_type), self.overlay_stats) {
+            (Some(WorkerState::Running), Some(stats)) if *thread_type == ThreadType::GameOverlay => {
+                format!("running, {:.0} fps", stats.fps)
+            }
+            (Some(state), _) => state.to_string(),
+            (None, _) => "stopped".to_string(),
+        }
     }
 
     pub async fn check_overlay_status(&mut self) {
@@ -206,13 +584,30 @@ impl App {
             if !self.overlay_was_active {
                 self.thread_handler.start_thread(self.game_window, ThreadType::GameOverlay).await;
                 self.overlay_was_active = true;
-                self.log_message("[PROCESS] Overlay started!");
+                self.log_message(LogLevel::Info, "PROCESS", "Overlay started!");
             }
         } else {
             if self.overlay_was_active {
-                self.thread_handler.stop_thread(&ThreadType::GameOverlay).await;
+                let outcome = self.thread_handler.stop_thread(&ThreadType::GameOverlay).await;
+                self.worker_states.remove(&ThreadType::GameOverlay);
+                self.overlay_stats = None;
                 self.overlay_was_active = false;
-                self.log_message("[PROCESS] Game overlay stopped.");
+                match outcome {
+                    StopOutcome::Stopped(took) => self.log_message(
+                        LogLevel::Info,
+                        "PROCESS",
+                        format!("Game overlay stopped in {} ms.", took.as_millis()),
+                    ),
+                    StopOutcome::TimedOut(timeout) => self.log_message(
+                        LogLevel::Warn,
+                        "PROCESS",
+                        format!(
+                            "Game overlay did not stop within {} ms and was killed.",
+                            timeout.as_millis()
+                        ),
+                    ),
+                    StopOutcome::NotRunning => self.log_message(LogLevel::Info, "PROCESS", "Game overlay stopped."),
+                }
             }
         }
     }
@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use chrono::{DateTime, Local};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "aim2go")]
@@ -20,15 +22,18 @@ use clap::{Parser, Subcommand};
                                                        .dWi   `88E             
                                                        4888~  J8%              
                                                         ^"===*"`              "#, long_about = None)]
-
 pub struct Cli {
 	#[command(subcommand)]
 	pub command: Option<Commands>,
 
-	/// Lists avalible games
+	/// Lists available games and flags broken ones
 	#[arg(short, long)]
 	pub list: bool,
 
+	/// Directory holding the games (overrides AIM2GO_HOME and the default data dir)
+	#[arg(long, global = true)]
+	pub root: Option<PathBuf>,
+
 }
 
 #[derive(Subcommand)]
@@ -47,4 +52,79 @@ pub enum Commands {
 	Attach {
 	   game: String,
 	}, 
+
+	/// Prints the log of a game's attach session
+	Logs {
+	   game: String,
+
+	   /// Keeps printing new entries as the session logs them
+	   #[arg(short, long)]
+	   follow: bool,
+
+	   /// Session to print, counting back from 1 for the newest
+	   #[arg(short, long, default_value_t = 1)]
+	   session: usize,
+	},
+
+	/// Runs the overlay window; started by `attach`, not meant to be run by hand
+	#[command(hide = true)]
+	Overlay {
+	   #[arg(long)]
+	   game: String,
+
+	   /// Start of the attach session, shown by the HUD
+	   #[arg(long)]
+	   session_start: Option<DateTime<Local>>,
+	},
+
+	/// Manages a games config
+	Config {
+	   #[command(subcommand)]
+	   command: ConfigCommands,
+	},
+
+	/// Shares a games crosshair through share codes
+	Crosshair {
+	   #[command(subcommand)]
+	   command: CrosshairCommands,
+	},
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+	/// Migrates a games config to the current schema version
+	Migrate {
+	   game: String,
+	},
+}
+
+#[derive(Subcommand)]
+pub enum CrosshairCommands {
+	/// Prints a share code for a games crosshair
+	Export {
+	   game: String,
+	},
+
+	/// Replaces a games crosshair with the one in a share code
+	Import {
+	   game: String,
+	   code: String,
+	},
+
+	/// Draws a games crosshair into a PNG, the way the overlay shows it
+	Preview {
+	   game: String,
+
+	   /// PNG file to write
+	   #[arg(short, long)]
+	   out: PathBuf,
+
+	   /// Width of the image in pixels
+	   #[arg(long, default_value_t = 128)]
+	   width: u32,
+
+	   /// Height of the image in pixels
+	   #[arg(long, default_value_t = 128)]
+	   height: u32,
+	},
 }
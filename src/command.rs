@@ -0,0 +1,299 @@
+use std::path::PathBuf;
+use aim2go::config::{CrosshairType, OverlayLayer};
+use aim2go::theme::Rgb;
+
+use crate::log::LogLevel;
+
+/// A command typed into the Execute prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Lists every command, or describes one.
+    Help(Option<String>),
+    /// Starts or stops the game overlay.
+    Overlay(bool),
+    /// Shows or hides what the running overlay draws.
+    OverlayVisible(bool),
+    /// Drops the previews sent to the overlay, going back to the config.
+    OverlayReset,
+    /// Previews another crosshair type in the overlay, keeping the other settings.
+    Crosshair(CrosshairType),
+    /// Logs a share code for the crosshair in the config.
+    CrosshairExport,
+    /// Saves the crosshair in a share code to the config.
+    CrosshairImport(String),
+    /// Previews a crosshair colour in the overlay.
+    Color(Rgb),
+    /// Previews the overlay with only these layers.
+    Layers(Vec<OverlayLayer>),
+    /// Reloads `config.yaml` and `app.yaml` right away.
+    Reload,
+    /// Logs the state of the session.
+    Status,
+    /// Empties the execution log.
+    Clear,
+    /// Writes the execution log to a file.
+    SaveLog(Option<PathBuf>),
+    /// Shows only the given log levels; every level if empty.
+    Filter(Vec<LogLevel>),
+    /// Shows only log entries matching a regex; every entry if `None`.
+    Search(Option<String>),
+}
+
+/// Description of a command, used for parsing, help and completion.
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// Arguments as shown in help, e.g. `on|off`.
+    pub usage: &'static str,
+    pub summary: &'static str,
+    /// Fixed values the first argument can take, offered by tab completion.
+    pub choices: &'static [&'static str],
+}
+
+/// Every command the prompt understands.
+pub const COMMANDS: [CommandSpec; 11] = [
+    CommandSpec {
+        name: "help",
+        usage: "[command]",
+        summary: "List the commands, or describe one",
+        choices: &[
+            "help", "overlay", "crosshair", "color", "layers", "reload", "status", "clear", "save-log", "filter",
+            "search",
+        ],
+    },
+    CommandSpec {
+        name: "overlay",
+        usage: "on|off|show|hide|reset",
+        summary: "Start or stop the game overlay, show or hide it, or drop the previews sent to it",
+        choices: &["on", "off", "show", "hide", "reset"],
+    },
+    CommandSpec {
+        name: "crosshair",
+        usage: "<type>|export|import <code>",
+        summary: "Preview a crosshair type, or share the crosshair through a share code",
+        choices: &["dot", "cross", "circle", "t", "crosswithdot", "custom", "export", "import"],
+    },
+    CommandSpec {
+        name: "color",
+        usage: "<colour>",
+        summary: "Preview a crosshair colour, e.g. #ff0000, in the running overlay",
+        choices: &[],
+    },
+    CommandSpec {
+        name: "layers",
+        usage: "none|<layer>...",
+        summary: "Preview the running overlay with only the given layers",
+        choices: &["none", "HUD", "Crosshair", "ESP", "ActiveRadius"],
+    },
+    CommandSpec {
+        name: "reload",
+        usage: "",
+        summary: "Reload config.yaml and app.yaml now",
+        choices: &[],
+    },
+    CommandSpec {
+        name: "status",
+        usage: "",
+        summary: "Show the game, window, overlay and config in use",
+        choices: &[],
+    },
+    CommandSpec {
+        name: "clear",
+        usage: "",
+        summary: "Clear the execution log",
+        choices: &[],
+    },
+    CommandSpec {
+        name: "save-log",
+        usage: "[path]",
+        summary: "Save the execution log, by default to the game's logs/ directory",
+        choices: &[],
+    },
+    CommandSpec {
+        name: "filter",
+        usage: "all|<level>...",
+        summary: "Show only the given levels: debug, info, warn, error",
+        choices: &["all", "debug", "info", "warn", "error"],
+    },
+    CommandSpec {
+        name: "search",
+        usage: "[regex]",
+        summary: "Show only entries matching a regex, or every entry if none is given",
+        choices: &[],
+    },
+];
+
+impl CommandSpec {
+    /// Looks up a command by name, with or without its leading `/`.
+    pub fn find(name: &str) -> Option<&'static CommandSpec> {
+        let name = name.strip_prefix('/').unwrap_or(name);
+        COMMANDS.iter().find(|spec| spec.name == name)
+    }
+
+    /// The command and its arguments as shown in help, e.g. `/overlay on|off`.
+    pub fn synopsis(&self) -> String {
+        if self.usage.is_empty() {
+            format!("/{}", self.name)
+        } else {
+            format!("/{} {}", self.name, self.usage)
+        }
+    }
+}
+
+impl Command {
+    /// Parses a line typed into the prompt.
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("Type a command, or /help to list them.".to_string());
+        };
+        let spec = CommandSpec::find(name)
+            .ok_or_else(|| format!("Unknown command `{}`. Type /help to list the commands.", name))?;
+        let args: Vec<&str> = words.collect();
+        let usage_error = || format!("Usage: {}", spec.synopsis());
+
+        if spec.name == "search" {
+            // The pattern may contain spaces, so take the rest of the line verbatim.
+            let pattern = line.trim().split_once(char::is_whitespace).map(|(_, rest)| rest.trim().to_string());
+            return Ok(Command::Search(pattern));
+        }
+        if spec.name == "color" {
+            // `rgb(r, g, b)` contains spaces as well.
+            let colour = line.trim().split_once(char::is_whitespace).ok_or_else(usage_error)?.1;
+            return Ok(Command::Color(colour.parse()?));
+        }
+
+        let command = match (spec.name, args.as_slice()) {
+            ("help", []) => Command::Help(None),
+            ("help", [topic]) => Command::Help(Some(topic.trim_start_matches('/').to_string())),
+            ("overlay", ["on"]) => Command::Overlay(true),
+            ("overlay", ["off"]) => Command::Overlay(false),
+            ("overlay", ["show"]) => Command::OverlayVisible(true),
+            ("overlay", ["hide"]) => Command::OverlayVisible(false),
+            ("overlay", ["reset"]) => Command::OverlayReset,
+            ("crosshair", ["export"]) => Command::CrosshairExport,
+            ("crosshair", ["import", code]) => Command::CrosshairImport(code.to_string()),
+            ("crosshair", [crosshair_type]) if *crosshair_type != "import" => {
+                Command::Crosshair(crosshair_type.parse()?)
+            }
+            ("layers", ["none"]) => Command::Layers(Vec::new()),
+            ("layers", layers) if !layers.is_empty() => {
+                Command::Layers(layers.iter().map(|layer| layer.parse()).collect::<Result<_, _>>()?)
+            }
+            ("reload", []) => Command::Reload,
+            ("status", []) => Command::Status,
+            ("clear", []) => Command::Clear,
+            ("save-log", []) => Command::SaveLog(None),
+            ("save-log", [path]) => Command::SaveLog(Some(PathBuf::from(path))),
+            ("filter", ["all"]) => Command::Filter(Vec::new()),
+            ("filter", levels) if !levels.is_empty() => {
+                Command::Filter(levels.iter().map(|level| level.parse()).collect::<Result<_, _>>()?)
+            }
+            _ => return Err(usage_error()),
+        };
+        Ok(command)
+    }
+}
+
+/// Returns the completions of the word being typed at the end of `line`.
+///
+/// The first word completes to command names, the second to the command's choices.
+pub fn completions(line: &str) -> Vec<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let typing_new_word = line.ends_with(char::is_whitespace);
+
+    match (words.as_slice(), typing_new_word) {
+        ([], _) => COMMANDS.iter().map(|spec| format!("/{}", spec.name)).collect(),
+        ([name], false) => {
+            let prefix = name.strip_prefix('/').unwrap_or(name);
+            COMMANDS
+                .iter()
+                .filter(|spec| spec.name.starts_with(prefix))
+                .map(|spec| format!("/{}", spec.name))
+                .collect()
+        }
+        ([name], true) | ([name, _], false) => {
+            let Some(spec) = CommandSpec::find(name) else {
+                return Vec::new();
+            };
+            let prefix = if typing_new_word { "" } else { words[1] };
+            spec.choices
+                .iter()
+                .filter(|choice| choice.starts_with(prefix))
+                .map(|choice| choice.to_string())
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Command {
+        Command::parse(line).unwrap_or_else(|e| panic!("{:?} failed to parse: {}", line, e))
+    }
+
+    #[test]
+    fn parses_commands_with_and_without_a_slash() {
+        assert_eq!(parse("/help"), Command::Help(None));
+        assert_eq!(parse("help /overlay"), Command::Help(Some("overlay".to_string())));
+        assert_eq!(parse("/overlay on"), Command::Overlay(true));
+        assert_eq!(parse("  /overlay   hide "), Command::OverlayVisible(false));
+        assert_eq!(parse("/overlay reset"), Command::OverlayReset);
+        assert_eq!(parse("/layers none"), Command::Layers(Vec::new()));
+        assert_eq!(parse("/layers hud crosshair"), Command::Layers(vec![OverlayLayer::Hud, OverlayLayer::Crosshair]));
+        assert_eq!(parse("/filter all"), Command::Filter(Vec::new()));
+        assert_eq!(parse("/filter warn error"), Command::Filter(vec![LogLevel::Warn, LogLevel::Error]));
+        assert_eq!(parse("/save-log"), Command::SaveLog(None));
+        assert_eq!(parse("/save-log out.log"), Command::SaveLog(Some(PathBuf::from("out.log"))));
+    }
+
+    #[test]
+    fn parses_crosshair_commands() {
+        assert_eq!(parse("/crosshair crosswithdot"), Command::Crosshair(CrosshairType::CrossWithDot));
+        assert_eq!(parse("/crosshair export"), Command::CrosshairExport);
+        assert_eq!(parse("/crosshair import AIM2GO-1-abc"), Command::CrosshairImport("AIM2GO-1-abc".to_string()));
+        assert!(Command::parse("/crosshair import").is_err());
+        assert!(Command::parse("/crosshair square").is_err());
+    }
+
+    #[test]
+    fn colours_and_search_patterns_keep_their_spaces() {
+        assert_eq!(parse("/color rgb(1, 2, 3)"), Command::Color(Rgb(1, 2, 3)));
+        assert_eq!(parse("/color #ff0000"), Command::Color(Rgb(255, 0, 0)));
+        assert!(Command::parse("/color").is_err());
+        assert!(Command::parse("/color rgb(1, 2)").is_err());
+        assert_eq!(parse("/search overlay  stopped "), Command::Search(Some("overlay  stopped".to_string())));
+        assert_eq!(parse("/search"), Command::Search(None));
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_bad_arguments() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("/launch").unwrap_err().contains("Unknown command `/launch`"));
+        assert_eq!(Command::parse("/overlay maybe").unwrap_err(), "Usage: /overlay on|off|show|hide|reset");
+        assert_eq!(Command::parse("/status now").unwrap_err(), "Usage: /status");
+        assert!(Command::parse("/filter loud").is_err());
+    }
+
+    #[test]
+    fn completes_command_names() {
+        assert_eq!(completions("").len(), COMMANDS.len());
+        assert_eq!(completions("/s"), ["/status", "/save-log", "/search"]);
+        assert_eq!(completions("/cr"), ["/crosshair"]);
+        assert!(completions("/x").is_empty());
+    }
+
+    #[test]
+    fn completes_the_first_argument_from_its_choices() {
+        assert_eq!(completions("/overlay "), ["on", "off", "show", "hide", "reset"]);
+        assert_eq!(completions("/overlay o"), ["on", "off"]);
+        assert_eq!(completions("/crosshair c"), ["cross", "circle", "crosswithdot", "custom"]);
+        assert!(completions("/color rgb(1, 2, 3)").is_empty());
+        assert!(completions("/crosshair import AIM2GO").is_empty());
+        assert!(completions("/crosshair import ").is_empty());
+        assert!(completions("/unknown ").is_empty());
+    }
+}
@@ -1,50 +1,554 @@
-use serde::{Deserialize, Serialize};
+use chrono::Local;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_yaml::Value;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 
-/// Configuration structure for both app-wide and game-specific settings.
+pub mod error;
+pub mod keybind;
+pub mod migrate;
+pub mod watch;
+
+use crate::theme::{Rgb, Theme};
+use crate::workspace;
+
+pub use self::error::{ConfigError, ConfigWarning, WarningKind};
+pub use self::keybind::{ChordError, KeyChord, MouseChord};
+use self::migrate::Migration;
+
+/// Current version of the `config.yaml` schema.
+pub const CONFIG_VERSION: u32 = 4;
+
+/// Configuration structure stored in a game's `config.yaml`.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
-    pub app: AppConfig,
+    /// Schema version; files written before versioning was introduced have none.
+    #[serde(default)]
+    pub version: u32,
+    /// Per-game overrides of the global settings in `app.yaml`.
+    #[serde(default, skip_serializing_if = "AppOverrides::is_empty")]
+    pub app: AppOverrides,
     pub game: GameConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// App-wide settings, stored in `app.yaml` in the user config dir.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Milliseconds between ticks of the TUI
+    #[serde(deserialize_with = "deserialize_tick_rate")]
     pub tick_rate: u64,
-    pub theme: String,
+    pub theme: Theme,
     pub ascii_art: String,
+    /// Key chords of the TUI; setting this replaces the whole default keymap.
+    pub tui_keys: HashMap<String, TuiCommand>,
+    pub session_log: SessionLogConfig,
+    /// What happens when a worker, such as the overlay, stops on its own.
+    pub worker_restart: RestartConfig,
+    /// How long stopping a worker waits for it to finish before giving up on it.
+    pub worker_stop_timeout_ms: u64,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// How each attach session is logged to `<game>/logs/<timestamp>.log`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SessionLogConfig {
+    pub format: LogFormat,
+    /// Oldest session logs are deleted when a session starts with more than this many.
+    pub max_sessions: Option<usize>,
+    /// Oldest session logs are deleted when a session starts and they take more bytes than this.
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for SessionLogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            max_sessions: Some(20),
+            max_bytes: Some(10 * 1024 * 1024),
+        }
+    }
+}
+
+/// When and how soon a worker that stopped on its own is started again.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RestartConfig {
+    pub policy: RestartPolicy,
+    /// Delay before the first restart, doubled for each consecutive one
+    pub backoff_ms: u64,
+    /// Longest delay between restarts
+    pub max_backoff_ms: u64,
+    /// Consecutive restarts before giving up; unlimited if unset
+    pub max_restarts: Option<u32>,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::OnFailure,
+            backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            max_restarts: Some(5),
+        }
+    }
+}
+
+impl RestartConfig {
+    /// Returns how long to wait before restarting a worker that stopped, or `None` to leave it stopped.
+    ///
+    /// `restarts` counts the consecutive restarts so far.
+    pub fn delay(&self, failed: bool, restarts: u32) -> Option<Duration> {
+        let restart = match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => failed,
+            RestartPolicy::Always => true,
+        };
+        if !restart || self.max_restarts.is_some_and(|max| restarts >= max) {
+            return None;
+        }
+        let backoff = self.backoff_ms.saturating_mul(1 << restarts.min(16));
+        Some(Duration::from_millis(backoff.min(self.max_backoff_ms)))
+    }
+}
+
+/// When a worker is restarted after stopping on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RestartPolicy {
+    Never,
+    /// Only after it returned an error or panicked
+    OnFailure,
+    Always,
+}
+
+/// Format of the session log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LogFormat {
+    /// One line per entry, as shown in the TUI
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Per-game overrides of [`AppConfig`]; unset fields fall back to `app.yaml`.
+///
+/// Every field of [`AppConfig`] has an optional counterpart here.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AppOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_tick_rate_override")]
+    pub tick_rate: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ascii_art: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tui_keys: Option<HashMap<String, TuiCommand>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_log: Option<SessionLogConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_restart: Option<RestartConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_stop_timeout_ms: Option<u64>,
+}
+
+impl AppOverrides {
+    /// Checks if no setting is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.tick_rate.is_none()
+            && self.theme.is_none()
+            && self.ascii_art.is_none()
+            && self.tui_keys.is_none()
+            && self.session_log.is_none()
+            && self.worker_restart.is_none()
+            && self.worker_stop_timeout_ms.is_none()
+    }
+}
+
+fn deserialize_tick_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let tick_rate = u64::deserialize(deserializer)?;
+    if tick_rate == 0 {
+        return Err(de::Error::custom("`tick_rate` must be at least 1 millisecond"));
+    }
+    Ok(tick_rate)
+}
+
+fn deserialize_tick_rate_override<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    deserialize_tick_rate(deserializer).map(Some)
+}
+
+impl From<AppConfig> for AppOverrides {
+    fn from(app: AppConfig) -> Self {
+        Self {
+            tick_rate: Some(app.tick_rate),
+            theme: Some(app.theme),
+            ascii_art: Some(app.ascii_art),
+            tui_keys: Some(app.tui_keys),
+            session_log: Some(app.session_log),
+            worker_restart: Some(app.worker_restart),
+            worker_stop_timeout_ms: Some(app.worker_stop_timeout_ms),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Applies a game's overrides on top of these settings.
+    pub fn with_overrides(self, overrides: &AppOverrides) -> Self {
+        Self {
+            tick_rate: overrides.tick_rate.unwrap_or(self.tick_rate),
+            theme: overrides.theme.unwrap_or(self.theme),
+            ascii_art: overrides.ascii_art.clone().unwrap_or(self.ascii_art),
+            tui_keys: overrides.tui_keys.clone().unwrap_or(self.tui_keys),
+            session_log: overrides.session_log.clone().unwrap_or(self.session_log),
+            worker_restart: overrides.worker_restart.clone().unwrap_or(self.worker_restart),
+            worker_stop_timeout_ms: overrides.worker_stop_timeout_ms.unwrap_or(self.worker_stop_timeout_ms),
+        }
+    }
+}
+
+/// Settings in effect for a game: `app.yaml` merged with the game's overrides,
+/// plus the game's own settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EffectiveConfig {
+    pub app: AppConfig,
+    pub game: GameConfig,
+}
+
+impl EffectiveConfig {
+    /// Merges the global app settings with a game's config.
+    pub fn resolve(app: AppConfig, config: Config) -> Self {
+        Self {
+            app: app.with_overrides(&config.app),
+            game: config.game,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GameConfig {
     pub overlay: OverlayConfig,
     pub keybinds: KeybindConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OverlayConfig {
-    pub render: Vec<String>,
-    pub crosshair_type: CrosshairType,
-    pub crosshair_size: f32,
+    /// Enabled layers; each layer may be listed once.
+    #[serde(deserialize_with = "deserialize_layers")]
+    pub layers: Vec<LayerConfig>,
+    #[serde(deserialize_with = "deserialize_crosshair")]
+    pub crosshair: CrosshairConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A layer the overlay can draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OverlayLayer {
+    /// Game name, session time and frame rate
+    #[serde(rename = "HUD")]
+    Hud,
+    Crosshair,
+    #[serde(rename = "ESP")]
+    Esp,
+    ActiveRadius,
+}
+
+impl OverlayLayer {
+    /// Every layer, in display order.
+    pub const ALL: [OverlayLayer; 4] = [
+        OverlayLayer::Hud,
+        OverlayLayer::Crosshair,
+        OverlayLayer::Esp,
+        OverlayLayer::ActiveRadius,
+    ];
+
+    /// Name of the layer as written in `config.yaml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            OverlayLayer::Hud => "HUD",
+            OverlayLayer::Crosshair => "Crosshair",
+            OverlayLayer::Esp => "ESP",
+            OverlayLayer::ActiveRadius => "ActiveRadius",
+        }
+    }
+
+    /// Settings a layer gets when it is enabled.
+    pub fn default_config(self) -> LayerConfig {
+        let (z_order, opacity, anchor) = match self {
+            OverlayLayer::Hud => (20, 0.8, Anchor::TopLeft),
+            OverlayLayer::Crosshair => (10, 1.0, Anchor::Center),
+            OverlayLayer::Esp => (0, 1.0, Anchor::Center),
+            OverlayLayer::ActiveRadius => (5, 0.5, Anchor::Center),
+        };
+        LayerConfig {
+            layer: self,
+            z_order,
+            opacity,
+            anchor,
+        }
+    }
+}
+
+impl fmt::Display for OverlayLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for OverlayLayer {
+    type Err = String;
+
+    /// Parses a layer by name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OverlayLayer::ALL
+            .into_iter()
+            .find(|layer| layer.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = OverlayLayer::ALL.iter().map(|layer| layer.name()).collect();
+                format!("`{}` is not an overlay layer, expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Point of the overlay window a layer is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum Anchor {
+    #[default]
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How an enabled overlay layer is drawn.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LayerConfig {
+    pub layer: OverlayLayer,
+    /// Layers with a higher z-order are drawn on top.
+    #[serde(default)]
+    pub z_order: i32,
+    /// From 0 (invisible) to 1 (opaque).
+    #[serde(default = "default_opacity", deserialize_with = "deserialize_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn deserialize_opacity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let opacity = f32::deserialize(deserializer)?;
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(de::Error::custom(format!("opacity must be between 0 and 1, found {}", opacity)));
+    }
+    Ok(opacity)
+}
+
+fn deserialize_layers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<LayerConfig>, D::Error> {
+    let layers = Vec::<LayerConfig>::deserialize(deserializer)?;
+    for (index, layer) in layers.iter().enumerate() {
+        if layers[..index].iter().any(|other| other.layer == layer.layer) {
+            return Err(de::Error::custom(format!("layer `{}` is listed more than once", layer.layer)));
+        }
+    }
+    Ok(layers)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum CrosshairType {
     Dot,
     Cross,
+    /// Ring of radius `size`
+    Circle,
+    /// Cross without its top arm
+    T,
+    CrossWithDot,
+    /// Made of the crosshair's `primitives`
+    Custom,
 }
 
+impl CrosshairType {
+    /// Every crosshair type, in display order.
+    pub const ALL: [CrosshairType; 6] = [
+        CrosshairType::Dot,
+        CrosshairType::Cross,
+        CrosshairType::Circle,
+        CrosshairType::T,
+        CrosshairType::CrossWithDot,
+        CrosshairType::Custom,
+    ];
 
-#[derive(Debug, Deserialize, Serialize)]
+    /// Does the crosshair have arms?
+    pub fn has_arms(&self) -> bool {
+        matches!(self, CrosshairType::Cross | CrosshairType::T | CrosshairType::CrossWithDot)
+    }
+
+    /// Does the crosshair use `size`, as the radius of its dot or ring?
+    pub fn has_radius(&self) -> bool {
+        matches!(self, CrosshairType::Dot | CrosshairType::Circle | CrosshairType::CrossWithDot)
+    }
+}
+
+/// Shape and style of the overlay crosshair.
+///
+/// Lengths are in pixels; missing keys take their default value.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CrosshairConfig {
+    #[serde(rename = "type")]
+    pub crosshair_type: CrosshairType,
+    /// Radius of the dot or ring
+    pub size: f32,
+    /// Distance from the centre to where the arms start
+    pub gap: f32,
+    pub arm_length: f32,
+    /// Width of the arms, rings and lines
+    pub thickness: f32,
+    pub outline_color: Rgb,
+    /// Width of the outline around every shape; 0 draws none
+    pub outline_width: f32,
+    /// From 0 (invisible) to 1 (opaque); the outline is always opaque
+    pub fill_opacity: f32,
+    /// Shift of the centre from the layer's anchor, as `[x, y]`
+    pub offset: [f32; 2],
+    /// Shapes of a `Custom` crosshair, relative to its centre
+    pub primitives: Vec<CrosshairPrimitive>,
+}
+
+impl CrosshairConfig {
+    /// Largest size, gap, arm length or primitive coordinate.
+    pub const MAX_LENGTH: f32 = 100.0;
+    /// Largest thickness or outline width.
+    pub const MAX_WIDTH: f32 = 20.0;
+    /// Largest shift of the centre in either direction.
+    pub const MAX_OFFSET: f32 = 1000.0;
+    /// Most primitives a `Custom` crosshair can have.
+    pub const MAX_PRIMITIVES: usize = 32;
+
+    /// Checks that every setting is in range and the crosshair draws something.
+    pub fn validate(&self) -> Result<(), String> {
+        check_range("size", self.size, 0.0, Self::MAX_LENGTH)?;
+        check_range("gap", self.gap, 0.0, Self::MAX_LENGTH)?;
+        check_range("arm_length", self.arm_length, 0.0, Self::MAX_LENGTH)?;
+        check_range("thickness", self.thickness, 0.0, Self::MAX_WIDTH)?;
+        check_range("outline_width", self.outline_width, 0.0, Self::MAX_WIDTH)?;
+        check_range("fill_opacity", self.fill_opacity, 0.0, 1.0)?;
+        check_range("offset", self.offset[0], -Self::MAX_OFFSET, Self::MAX_OFFSET)?;
+        check_range("offset", self.offset[1], -Self::MAX_OFFSET, Self::MAX_OFFSET)?;
+        if self.primitives.len() > Self::MAX_PRIMITIVES {
+            return Err(format!("a crosshair can have at most {} `primitives`", Self::MAX_PRIMITIVES));
+        }
+        for primitive in &self.primitives {
+            primitive.validate()?;
+        }
+
+        let crosshair_type = &self.crosshair_type;
+        if crosshair_type.has_radius() && self.size == 0.0 {
+            return Err(format!("a {:?} crosshair needs a `size` above 0", crosshair_type));
+        }
+        if (crosshair_type.has_arms() || *crosshair_type == CrosshairType::Circle) && self.thickness == 0.0 {
+            return Err(format!("a {:?} crosshair needs a `thickness` above 0", crosshair_type));
+        }
+        if crosshair_type.has_arms() && self.arm_length == 0.0 {
+            return Err(format!("a {:?} crosshair needs an `arm_length` above 0", crosshair_type));
+        }
+        if *crosshair_type == CrosshairType::Custom && self.primitives.is_empty() {
+            return Err("a Custom crosshair needs at least one entry in `primitives`".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for CrosshairConfig {
+    fn default() -> Self {
+        CrosshairConfig {
+            crosshair_type: CrosshairType::Dot,
+            size: 5.0,
+            gap: 3.0,
+            arm_length: 6.0,
+            thickness: 2.0,
+            outline_color: Rgb(0, 0, 0),
+            outline_width: 1.0,
+            fill_opacity: 1.0,
+            offset: [0.0, 0.0],
+            primitives: Vec::new(),
+        }
+    }
+}
+
+/// A shape of a `Custom` crosshair; points are `[x, y]` from the crosshair centre.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "shape")]
+pub enum CrosshairPrimitive {
+    /// Filled circle
+    Dot { center: [f32; 2], radius: f32 },
+    /// Ring drawn with the crosshair thickness
+    Circle { center: [f32; 2], radius: f32 },
+    /// Line drawn with the crosshair thickness
+    Line { from: [f32; 2], to: [f32; 2] },
+}
+
+impl CrosshairPrimitive {
+    fn validate(&self) -> Result<(), String> {
+        let max = CrosshairConfig::MAX_LENGTH;
+        let points = match self {
+            CrosshairPrimitive::Dot { center, radius } | CrosshairPrimitive::Circle { center, radius } => {
+                check_range("radius", *radius, 0.0, max)?;
+                vec![center]
+            }
+            CrosshairPrimitive::Line { from, to } => vec![from, to],
+        };
+        for point in points {
+            check_range("primitive point", point[0], -max, max)?;
+            check_range("primitive point", point[1], -max, max)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be between {} and {}, found {}", name, min, max, value))
+    }
+}
+
+fn deserialize_crosshair<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CrosshairConfig, D::Error> {
+    let crosshair = CrosshairConfig::deserialize(deserializer)?;
+    crosshair.validate().map_err(de::Error::custom)?;
+    Ok(crosshair)
+}
+
+impl FromStr for CrosshairType {
+    type Err = String;
+
+    /// Parses a crosshair type by name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CrosshairType::ALL
+            .iter()
+            .find(|crosshair_type| format!("{:?}", crosshair_type).eq_ignore_ascii_case(s))
+            .cloned()
+            .ok_or_else(|| {
+                let names: Vec<String> = CrosshairType::ALL.iter().map(|t| format!("{:?}", t)).collect();
+                format!("`{}` is not a crosshair type, expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct KeybindConfig {
     pub keyboard: HashMap<String, Actions>,
     pub mouse: HashMap<String, Actions>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Actions {
     ClickCapture,
     ClassCaptureWheel,
@@ -53,9 +557,214 @@ pub enum Actions {
     Quit,
 }
 
-/// Returns the directory path for a specific game: `./<game>/`
+impl Actions {
+    /// Every action, in display order.
+    pub const ALL: [Actions; 5] = [
+        Actions::ClickCapture,
+        Actions::ClassCaptureWheel,
+        Actions::SnapAim,
+        Actions::CloseOverlay,
+        Actions::Quit,
+    ];
+}
+
+/// Commands the TUI keys can be bound to in `tui_keys`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TuiCommand {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// Opens the selected menu entry or edits the selected field.
+    Select,
+    /// Returns to the menu.
+    Back,
+    /// Stops the overlay and quits from any screen.
+    Quit,
+    ToggleOverlay,
+    Save,
+    Revert,
+    AddItem,
+    RemoveItem,
+    /// Focuses the command prompt of the Execute screen.
+    OpenPrompt,
+    PageUp,
+    PageDown,
+    /// Pauses or resumes following new entries of the execution log.
+    ToggleFollow,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            tick_rate: 60,
+            theme: Theme::default(),
+            ascii_art: r#"
+                                ____      ,----,                      
+               ,--,           ,'  , `.  .'   .' \                     
+             ,--.'|        ,-+-,.' _ |,----,'    |            ,---.  
+             |  |,      ,-+-. ;   , |||    :  .  ;,----._,.  '   ,'\ 
+   ,--.--.   `--'_     ,--.'|'   |  ||;    |.'  //   /  ' / /   /   |
+  /       \  ,' ,'|   |   |  ,', |  |,`----'/  ;|   :     |.   ; ,. :
+ .--.  .-. | '  | |   |   | /  | |--'   /  ;  / |   | .\  .'   | |: :
+  \__\/: . . |  | :   |   : |  | ,     ;  /  /-,.   ; ';  |'   | .; :
+  ," .--.; | '  : |__ |   : |  |/     /  /  /.`|'   .   . ||   :    |
+ /  /  ,.  | |  | '.'||   | |`-'    ./__;      : `---`-'| | \   \  / 
+;  :   .'   \;  :    ;|   ;/        |   :    .'  .'__/\_: |  `----'  
+|  ,     .-./|  ,   / '---'         ;   | .'     |   :    :          
+ `--`---'     ---`-'                `---'         \   \  /           
+                                                   `--`-'            "#.to_string(),
+            tui_keys: HashMap::from([
+                ("K".to_string(), TuiCommand::MoveUp),
+                ("UP".to_string(), TuiCommand::MoveUp),
+                ("J".to_string(), TuiCommand::MoveDown),
+                ("DOWN".to_string(), TuiCommand::MoveDown),
+                ("H".to_string(), TuiCommand::MoveLeft),
+                ("LEFT".to_string(), TuiCommand::MoveLeft),
+                ("L".to_string(), TuiCommand::MoveRight),
+                ("RIGHT".to_string(), TuiCommand::MoveRight),
+                ("ENTER".to_string(), TuiCommand::Select),
+                ("SPACE".to_string(), TuiCommand::Select),
+                ("ESC".to_string(), TuiCommand::Back),
+                ("Q".to_string(), TuiCommand::Quit),
+                ("CTRL+C".to_string(), TuiCommand::Quit),
+                ("O".to_string(), TuiCommand::ToggleOverlay),
+                ("S".to_string(), TuiCommand::Save),
+                ("CTRL+S".to_string(), TuiCommand::Save),
+                ("R".to_string(), TuiCommand::Revert),
+                ("A".to_string(), TuiCommand::AddItem),
+                ("D".to_string(), TuiCommand::RemoveItem),
+                ("DELETE".to_string(), TuiCommand::RemoveItem),
+                ("/".to_string(), TuiCommand::OpenPrompt),
+                ("PAGEUP".to_string(), TuiCommand::PageUp),
+                ("PAGEDOWN".to_string(), TuiCommand::PageDown),
+                ("F".to_string(), TuiCommand::ToggleFollow),
+            ]),
+            session_log: SessionLogConfig::default(),
+            worker_restart: RestartConfig::default(),
+            worker_stop_timeout_ms: 2000,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            app: AppOverrides::default(),
+            game: GameConfig {
+                overlay: OverlayConfig {
+                    layers: OverlayLayer::ALL.into_iter().map(OverlayLayer::default_config).collect(),
+                    crosshair: CrosshairConfig::default(),
+                },
+                keybinds: KeybindConfig {
+                    keyboard: HashMap::from([
+                        ("CTRL+Q".to_string(), Actions::ClassCaptureWheel),
+                        ("CTRL+ESC".to_string(), Actions::CloseOverlay),
+                        ("CTRL+SHIFT+ESC".to_string(), Actions::Quit),
+                    ]),
+                    mouse: HashMap::from([                    
+                        ("LMB".to_string(), Actions::ClickCapture),
+                        ("RMB".to_string(), Actions::SnapAim),
+                    ]),
+                },
+            },
+        }
+    }
+}
+
+/// A parsed config together with what happened while loading it.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: Config,
+    pub migration: Migration,
+    pub warnings: Vec<ConfigWarning>,
+}
+
+/// A game's effective config together with the warnings raised while loading it.
+#[derive(Debug)]
+pub struct ResolvedConfig {
+    pub config: EffectiveConfig,
+    pub warnings: Vec<ConfigWarning>,
+}
+
+/// Returns the path to the global `app.yaml` in the user config dir.
+fn get_app_config_file() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("aim2go")
+        .join("app.yaml")
+}
+
+/// Loads the global app settings, falling back to the defaults if `app.yaml` is missing.
+pub fn load_app_config() -> Result<AppConfig, ConfigError> {
+    Ok(read_app_config()?.0)
+}
+
+/// Loads the global app settings like [`load_app_config`], also returning unknown keys.
+pub fn read_app_config() -> Result<(AppConfig, Vec<ConfigWarning>), ConfigError> {
+    read_app_config_file(&get_app_config_file())
+}
+
+fn read_app_config_file(config_file: &Path) -> Result<(AppConfig, Vec<ConfigWarning>), ConfigError> {
+    if !config_file.exists() {
+        return Ok((AppConfig::default(), Vec::new()));
+    }
+
+    let content = fs::read_to_string(config_file).map_err(|e| ConfigError::io(config_file, e))?;
+    let value: Value = serde_yaml::from_str(&content).map_err(|e| ConfigError::parse(config_file, &content, &e))?;
+    let app: AppConfig = serde_yaml::from_str(&content).map_err(|e| ConfigError::parse(config_file, &content, &e))?;
+
+    let known = serde_yaml::to_value(AppConfig::default()).unwrap_or_default();
+    let mut warnings = error::unknown_keys(config_file, &content, &value, &known);
+    warnings.extend(error::theme_warning(config_file, &content, "theme", value.get("theme")));
+    warnings.extend(keybind::tui_key_warnings(config_file, &content, "tui_keys", &app.tui_keys));
+
+    Ok((app, warnings))
+}
+
+/// Saves the global app settings to `app.yaml`.
+pub fn save_app_config(app: &AppConfig) -> Result<(), ConfigError> {
+    write_yaml(&get_app_config_file(), app)
+}
+
+/// Writes `app.yaml` with the default settings unless it already exists.
+pub fn create_default_app_config() -> Result<(), ConfigError> {
+    create_default_app_config_file(&get_app_config_file())
+}
+
+fn create_default_app_config_file(config_file: &Path) -> Result<(), ConfigError> {
+    if config_file.exists() {
+        return Ok(());
+    }
+    write_yaml(config_file, &AppConfig::default())
+}
+
+/// Loads `app.yaml` and a game's config and merges them into the settings in effect.
+pub fn resolve_game_config(game: &str) -> Result<ResolvedConfig, ConfigError> {
+    resolve_game_config_with(&get_app_config_file(), game)
+}
+
+/// Resolves a game's config like [`resolve_game_config`], with the app settings from `app_file`.
+fn resolve_game_config_with(app_file: &Path, game: &str) -> Result<ResolvedConfig, ConfigError> {
+    let (app, mut warnings) = read_app_config_file(app_file)?;
+    let loaded = read_game_config(game)?;
+    warnings.extend(loaded.warnings);
+
+    Ok(ResolvedConfig {
+        config: EffectiveConfig::resolve(app, loaded.config),
+        warnings,
+    })
+}
+
+/// Loads the settings in effect for a game, see [`resolve_game_config`].
+pub fn load_effective_config(game: &str) -> Result<EffectiveConfig, ConfigError> {
+    Ok(resolve_game_config(game)?.config)
+}
+
+/// Returns the directory path for a specific game: `<root>/<game>/`
 fn get_game_config_dir(game: &str) -> PathBuf {
-    PathBuf::from(".").join(game)
+    workspace::game_dir(game)
 }
 
 /// Returns the path to a game's `config.yaml` file inside its directory.
@@ -65,93 +774,274 @@ fn get_game_config_file(game: &str) -> PathBuf {
 
 /// Loads the configuration for a specific game.
 /// If the game directory or config file is missing, it creates them with defaults.
-pub fn load_game_config(game: &str) -> io::Result<Config> {
+/// Configs written in an older schema are migrated in memory; the file is left as is.
+pub fn load_game_config(game: &str) -> Result<Config, ConfigError> {
+    Ok(read_game_config(game)?.config)
+}
+
+/// Loads a game's config like [`load_game_config`], also returning what migration
+/// ran and which unknown keys were ignored.
+pub fn read_game_config(game: &str) -> Result<LoadedConfig, ConfigError> {
     let config_file = get_game_config_file(game);
 
     if !config_file.exists() {
         create_default_game_config(game)?;
     }
 
-    let config_content = fs::read_to_string(&config_file)?;
-    let config: Config = serde_yaml::from_str(&config_content)
-        .unwrap_or_else(|_| panic!("Failed to parse config file: {:?}", config_file));
+    let config_content = fs::read_to_string(&config_file).map_err(|e| ConfigError::io(&config_file, e))?;
+    parse_config(&config_file, &config_content)
+}
 
-    Ok(config)
+/// Migrates a game's `config.yaml` to [`CONFIG_VERSION`], rewriting it and keeping a
+/// backup, and reports what changed.
+pub fn migrate_game_config(game: &str) -> Result<Migration, ConfigError> {
+    let mut loaded = read_game_config(game)?;
+    if loaded.migration.is_needed() {
+        let config_file = get_game_config_file(game);
+        loaded.migration.backup = backup_outdated_config(&config_file)?;
+        write_yaml(&config_file, &loaded.config)?;
+    }
+    Ok(loaded.migration)
+}
+
+/// Parses the contents of a `config.yaml`, migrating older schemas in memory.
+///
+/// `path` is only used to label errors and warnings.
+pub fn parse_config(path: &Path, content: &str) -> Result<LoadedConfig, ConfigError> {
+    let mut value: Value = serde_yaml::from_str(content).map_err(|e| ConfigError::parse(path, content, &e))?;
+    let migration = migrate::migrate(&mut value).map_err(|source| ConfigError::Migration {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let config: Config = if migration.is_needed() {
+        // Parse the migrated document as text so errors still name the offending key.
+        let migrated = serde_yaml::to_string(&value).map_err(|e| ConfigError::parse(path, content, &e))?;
+        serde_yaml::from_str(&migrated).map_err(|e| ConfigError::parse(path, &migrated, &e).without_location())?
+    } else {
+        serde_yaml::from_str(content).map_err(|e| ConfigError::parse(path, content, &e))?
+    };
+
+    // Every override is set so all of their keys count as known.
+    let known = Config {
+        app: AppConfig::default().into(),
+        ..Config::default()
+    };
+    let known = serde_yaml::to_value(known).unwrap_or_default();
+    let mut warnings = error::unknown_keys(path, content, &value, &known);
+    if migration.is_needed() {
+        warnings.push(ConfigWarning {
+            path: path.to_path_buf(),
+            key: "version".to_string(),
+            line: error::find_key_line(content, "version"),
+            kind: WarningKind::Outdated { version: migration.from },
+        });
+    }
+    let theme = value.get("app").and_then(|app| app.get("theme"));
+    warnings.extend(error::theme_warning(path, content, "app.theme", theme));
+    warnings.extend(keybind::chord_warnings(path, content, &config.game.keybinds));
+    if let Some(tui_keys) = &config.app.tui_keys {
+        warnings.extend(keybind::tui_key_warnings(path, content, "app.tui_keys", tui_keys));
+    }
+
+    Ok(LoadedConfig {
+        config,
+        migration,
+        warnings,
+    })
+}
+
+/// Copies a config file next to itself as `config.yaml.v<version>-<timestamp>.bak`.
+fn backup_config(config_file: &Path, version: u32) -> io::Result<PathBuf> {
+    let file_name = format!(
+        "config.yaml.v{}-{}.bak",
+        version,
+        Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let backup_file = config_file.with_file_name(file_name);
+    fs::copy(config_file, &backup_file)?;
+    Ok(backup_file)
+}
+
+/// Copies a `config.yaml` written in an older schema before it is overwritten, returning
+/// where the copy went; files that are current, missing or unreadable are not copied.
+fn backup_outdated_config(config_file: &Path) -> Result<Option<PathBuf>, ConfigError> {
+    let version = fs::read_to_string(config_file)
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<Value>(&content).ok())
+        .and_then(|value| migrate::config_version(&value).ok());
+    match version {
+        Some(version) if version < CONFIG_VERSION => backup_config(config_file, version)
+            .map(Some)
+            .map_err(|e| ConfigError::io(config_file, e)),
+        _ => Ok(None),
+    }
 }
 
 /// Saves the given configuration back to the game's `config.yaml` file.
-pub fn save_game_config(game: &str, config: &Config) -> io::Result<()> {
+///
+/// Saving upgrades a file written in an older schema, so a backup of it is kept first.
+pub fn save_game_config(game: &str, config: &Config) -> Result<(), ConfigError> {
     let config_file = get_game_config_file(game);
+    backup_outdated_config(&config_file)?;
+    write_yaml(&config_file, config)
+}
 
-    if let Some(parent_dir) = config_file.parent() {
-        fs::create_dir_all(parent_dir)?;
-    }
+/// Serializes a value to a YAML file, creating its parent directory if needed.
+fn write_yaml<T: Serialize>(config_file: &Path, value: &T) -> Result<(), ConfigError> {
+    let write = || -> io::Result<()> {
+        if let Some(parent_dir) = config_file.parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+
+        let yaml_data = serde_yaml::to_string(value).map_err(io::Error::other)?;
+        let mut file = fs::File::create(config_file)?;
+        file.write_all(yaml_data.as_bytes())
+    };
 
-    let yaml_data = serde_yaml::to_string(config).expect("Failed to serialize config");
-    let mut file = fs::File::create(&config_file)?;
-    file.write_all(yaml_data.as_bytes())?;
-    Ok(())
+    write().map_err(|e| ConfigError::io(config_file, e))
 }
 
 /// Creates a default configuration file for a new game.
-pub fn create_default_game_config(game: &str) -> io::Result<()> {
+pub fn create_default_game_config(game: &str) -> Result<(), ConfigError> {
     let game_dir = get_game_config_dir(game);
 
     if !game_dir.exists() {
-        fs::create_dir_all(&game_dir)?;
+        fs::create_dir_all(&game_dir).map_err(|e| ConfigError::io(&game_dir, e))?;
     }
 
-    let default_config = Config {
-        app: AppConfig {
-            tick_rate: 60,
-            theme: "magenta".to_string(),
-            ascii_art: r#"
-                                ____      ,----,                      
-               ,--,           ,'  , `.  .'   .' \                     
-             ,--.'|        ,-+-,.' _ |,----,'    |            ,---.  
-             |  |,      ,-+-. ;   , |||    :  .  ;,----._,.  '   ,'\ 
-   ,--.--.   `--'_     ,--.'|'   |  ||;    |.'  //   /  ' / /   /   |
-  /       \  ,' ,'|   |   |  ,', |  |,`----'/  ;|   :     |.   ; ,. :
- .--.  .-. | '  | |   |   | /  | |--'   /  ;  / |   | .\  .'   | |: :
-  \__\/: . . |  | :   |   : |  | ,     ;  /  /-,.   ; ';  |'   | .; :
-  ," .--.; | '  : |__ |   : |  |/     /  /  /.`|'   .   . ||   :    |
- /  /  ,.  | |  | '.'||   | |`-'    ./__;      : `---`-'| | \   \  / 
-;  :   .'   \;  :    ;|   ;/        |   :    .'  .'__/\_: |  `----'  
-|  ,     .-./|  ,   / '---'         ;   | .'     |   :    :          
- `--`---'     ---`-'                `---'         \   \  /           
-                                                   `--`-'            "#.to_string(),
-        },
-        game: GameConfig {
-            overlay: OverlayConfig {
-                render: vec![
-                    "HUD".to_string(),
-                    "Crosshair".to_string(),
-                    "ESP".to_string(),
-                    "ActiveRadius".to_string(),
-                ],
-                crosshair_type: CrosshairType::Dot,
-                crosshair_size: 5.0,
-            },
-            keybinds: KeybindConfig {
-                keyboard: HashMap::from([
-                    ("CTRL+Q".to_string(), Actions::ClassCaptureWheel),
-                    ("CTRL+ESC".to_string(), Actions::CloseOverlay),
-                    ("CTRL+SHIFT+ESC".to_string(), Actions::Quit),
-                ]),
-                mouse: HashMap::from([                    
-                    ("LMB".to_string(), Actions::ClickCapture),
-                    ("RMB".to_string(), Actions::SnapAim),
-                ]),
-            },
-        },
-    };
+    save_game_config(game, &Config::default())
+}
 
-    save_game_config(game, &default_config)
+/// Replaces a game's crosshair settings, keeping the rest of its config.
+pub fn update_crosshair(game: &str, crosshair: CrosshairConfig) -> Result<(), ConfigError> {
+    let mut config = load_game_config(game)?;
+    config.game.overlay.crosshair = crosshair;
+    save_game_config(game, &config)
 }
 
 /// Updates a game's configuration.
-pub fn update_game_config(game: &str, new_config: GameConfig) -> io::Result<()> {
+pub fn update_game_config(game: &str, new_config: GameConfig) -> Result<(), ConfigError> {
     let mut config = load_game_config(game)?;
     config.game = new_config;
     save_game_config(game, &config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const V3: &str = "\
+version: 3
+game:
+  overlay:
+    layers:
+    - layer: Crosshair
+    crosshair_type: Dot
+    crosshair_size: 4
+  keybinds:
+    keyboard: {}
+    mouse: {}
+";
+
+    /// Creates a game with `config` as its `config.yaml` in a workspace of its own.
+    pub(super) fn game_with(config: &str) -> String {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!("aim2go-config-tests-{}", std::process::id()));
+        workspace::set_root(root.clone());
+        assert_eq!(workspace::root(), root, "tests must not touch the real workspace");
+
+        let game = format!("game-{}", NEXT.fetch_add(1, Ordering::Relaxed));
+        fs::create_dir_all(workspace::game_dir(&game)).unwrap();
+        fs::write(get_game_config_file(&game), config).unwrap();
+        game
+    }
+
+    fn backups(game: &str) -> Vec<PathBuf> {
+        fs::read_dir(workspace::game_dir(game))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "bak"))
+            .collect()
+    }
+
+    #[test]
+    fn loading_an_old_config_migrates_in_memory_only() {
+        let game = game_with(V3);
+        let loaded = read_game_config(&game).unwrap();
+
+        assert_eq!(loaded.config.version, CONFIG_VERSION);
+        assert_eq!(loaded.config.game.overlay.crosshair.size, 4.0);
+        assert!(loaded
+            .warnings
+            .iter()
+            .any(|warning| matches!(warning.kind, WarningKind::Outdated { version: 3 })));
+        assert_eq!(fs::read_to_string(get_game_config_file(&game)).unwrap(), V3);
+        assert!(backups(&game).is_empty());
+    }
+
+    #[test]
+    fn migrate_game_config_rewrites_the_file_and_keeps_a_backup() {
+        let game = game_with(V3);
+        let migration = migrate_game_config(&game).unwrap();
+
+        assert_eq!((migration.from, migration.to), (3, CONFIG_VERSION));
+        let backup = migration.backup.expect("a backup is kept");
+        assert_eq!(fs::read_to_string(backup).unwrap(), V3);
+        let loaded = read_game_config(&game).unwrap();
+        assert!(!loaded.migration.is_needed());
+        assert!(loaded.warnings.is_empty());
+
+        // Nothing left to do, so nothing is written.
+        let again = migrate_game_config(&game).unwrap();
+        assert!(again.backup.is_none());
+        assert_eq!(backups(&game).len(), 1);
+    }
+
+    #[test]
+    fn tick_rate_of_zero_is_rejected() {
+        let error = serde_yaml::from_str::<AppConfig>("tick_rate: 0\n").unwrap_err();
+        assert!(error.to_string().contains("`tick_rate` must be at least 1 millisecond"), "{}", error);
+        assert_eq!(serde_yaml::from_str::<AppConfig>("tick_rate: 1\n").unwrap().tick_rate, 1);
+
+        let mut config = serde_yaml::to_value(Config::default()).unwrap();
+        config["app"] = serde_yaml::from_str("tick_rate: 0").unwrap();
+        let content = serde_yaml::to_string(&config).unwrap();
+        let error = parse_config(Path::new("config.yaml"), &content).unwrap_err();
+        let ConfigError::Parse { message, snippet, .. } = &error else {
+            panic!("expected a parse error, got {:?}", error);
+        };
+        assert_eq!(message, "`tick_rate` must be at least 1 millisecond");
+        assert_eq!(snippet.as_deref(), Some("  tick_rate: 0"));
+    }
+
+    #[test]
+    fn a_missing_app_config_falls_back_to_the_defaults_without_creating_it() {
+        let game = game_with(&serde_yaml::to_string(&Config::default()).unwrap());
+        let app_file = get_game_config_file(&game).with_file_name("app.yaml");
+
+        let (app, warnings) = read_app_config_file(&app_file).unwrap();
+        assert_eq!(app.tick_rate, AppConfig::default().tick_rate);
+        assert!(warnings.is_empty());
+        assert!(resolve_game_config_with(&app_file, &game).is_ok());
+        assert!(!app_file.exists());
+
+        create_default_app_config_file(&app_file).unwrap();
+        assert_eq!(read_app_config_file(&app_file).unwrap().0.tick_rate, AppConfig::default().tick_rate);
+
+        // An existing file is left alone.
+        fs::write(&app_file, "tick_rate: 10\n").unwrap();
+        create_default_app_config_file(&app_file).unwrap();
+        assert_eq!(read_app_config_file(&app_file).unwrap().0.tick_rate, 10);
+    }
+
+    #[test]
+    fn saving_over_an_old_config_keeps_a_backup() {
+        let game = game_with(V3);
+        update_crosshair(&game, CrosshairConfig::default()).unwrap();
+
+        assert_eq!(backups(&game).len(), 1);
+        assert!(!read_game_config(&game).unwrap().migration.is_needed());
+    }
+}
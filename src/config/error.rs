@@ -0,0 +1,499 @@
+use serde_yaml::Value;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::keybind::ChordError;
+use super::migrate::MigrationError;
+use super::CONFIG_VERSION;
+use crate::theme::{Theme, PRESETS};
+
+/// Maps whose keys are chosen by the user, so their keys are never reported as unknown.
+const FREE_FORM_KEYS: [&str; 4] = ["game.keybinds.keyboard", "game.keybinds.mouse", "app.tui_keys", "tui_keys"];
+
+/// Errors raised while reading or writing a game's `config.yaml`.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// The file is not valid YAML or does not match the config schema.
+    Parse {
+        path: PathBuf,
+        message: String,
+        /// Dotted path of the offending key, e.g. `game.overlay.crosshair_size`.
+        key: Option<String>,
+        /// 1-based line and column of the error in the file.
+        location: Option<(usize, usize)>,
+        /// The source line the error points at.
+        snippet: Option<String>,
+    },
+    /// The file uses a schema that cannot be migrated.
+    Migration { path: PathBuf, source: MigrationError },
+}
+
+impl ConfigError {
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Builds a [`ConfigError::Parse`] from a `serde_yaml` error raised on `content`.
+    ///
+    /// `serde_yaml` prefixes messages with the path of the offending key and suffixes
+    /// them with the location; both are split off into their own fields.
+    pub(crate) fn parse(path: &Path, content: &str, error: &serde_yaml::Error) -> Self {
+        let location = error.location().map(|location| (location.line(), location.column()));
+        let mut message = error.to_string();
+
+        if let Some((line, column)) = location {
+            let suffix = format!(" at line {} column {}", line, column);
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
+            }
+        }
+
+        let mut key = None;
+        if let Some((prefix, rest)) = message.split_once(": ") {
+            if !prefix.is_empty() && !prefix.contains(char::is_whitespace) && !prefix.contains('`') {
+                key = Some(prefix.to_string());
+                message = rest.to_string();
+            }
+        }
+
+        // Name the field itself for missing/unknown fields, not just its parent.
+        if message.starts_with("missing field") || message.starts_with("unknown field") {
+            if let Some(field) = message.split('`').nth(1) {
+                key = Some(match key {
+                    Some(parent) => format!("{}.{}", parent, field),
+                    None => field.to_string(),
+                });
+            }
+        }
+
+        let snippet = location
+            .and_then(|(line, _)| content.lines().nth(line.saturating_sub(1)))
+            .map(str::to_string);
+
+        ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+            key,
+            location,
+            snippet,
+        }
+    }
+
+    /// Drops the location of a parse error, for errors raised on a migrated document
+    /// whose line numbers no longer match the file.
+    pub(crate) fn without_location(self) -> Self {
+        match self {
+            ConfigError::Parse { path, message, key, .. } => ConfigError::Parse {
+                path,
+                message,
+                key,
+                location: None,
+                snippet: None,
+            },
+            other => other,
+        }
+    }
+
+    /// One-line form of the error, for logs and tables.
+    pub fn summary(&self) -> String {
+        match self {
+            ConfigError::Io { source, .. } => source.to_string(),
+            ConfigError::Parse { message, key, location, .. } => {
+                let mut summary = match key {
+                    Some(key) => format!("{}: {}", key, message),
+                    None => message.clone(),
+                };
+                if let Some((line, column)) = location {
+                    summary.push_str(&format!(" (line {}, column {})", line, column));
+                }
+                summary
+            }
+            ConfigError::Migration { source, .. } => source.to_string(),
+        }
+    }
+
+    /// Path of the config file the error refers to.
+    pub fn path(&self) -> &Path {
+        match self {
+            ConfigError::Io { path, .. }
+            | ConfigError::Parse { path, .. }
+            | ConfigError::Migration { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                writeln!(f, "error: {}", source)?;
+                write!(f, "  --> {}", path.display())
+            }
+            ConfigError::Parse {
+                path,
+                message,
+                key,
+                location,
+                snippet,
+            } => {
+                writeln!(f, "error: {}", message)?;
+                match location {
+                    Some((line, column)) => write!(f, "  --> {}:{}:{}", path.display(), line, column)?,
+                    None => write!(f, "  --> {}", path.display())?,
+                }
+                if let (Some((line, column)), Some(snippet)) = (location, snippet) {
+                    let gutter = " ".repeat(line.to_string().len());
+                    write!(f, "\n{} |", gutter)?;
+                    write!(f, "\n{} | {}", line, snippet)?;
+                    write!(f, "\n{} | {}^", gutter, " ".repeat(column.saturating_sub(1)))?;
+                }
+                if let Some(key) = key {
+                    write!(f, "\n   = key: {}", key)?;
+                }
+                Ok(())
+            }
+            ConfigError::Migration { path, source } => {
+                writeln!(f, "error: {}", source)?;
+                write!(f, "  --> {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Migration { source, .. } => Some(source),
+            ConfigError::Parse { .. } => None,
+        }
+    }
+}
+
+/// A problem in a config file that doesn't stop it from loading.
+#[derive(Debug, Clone)]
+pub struct ConfigWarning {
+    pub path: PathBuf,
+    /// Dotted path of the key the warning is about.
+    pub key: String,
+    /// 1-based line the key appears on, if it could be found.
+    pub line: Option<usize>,
+    pub kind: WarningKind,
+}
+
+/// What is wrong with the key a [`ConfigWarning`] points at.
+#[derive(Debug, Clone)]
+pub enum WarningKind {
+    /// The key is not part of the schema.
+    UnknownKey {
+        /// Closest known key at the same level.
+        suggestion: Option<String>,
+    },
+    /// A keybind chord could not be parsed; the binding is ignored.
+    InvalidChord(ChordError),
+    /// A keybind chord is spelled differently from, but equal to, another one.
+    DuplicateChord { other: String },
+    /// A keybind chord is never reached because a more general one also matches it.
+    ShadowedChord { by: String },
+    /// The file is in an older schema, which was migrated in memory only.
+    Outdated { version: u32 },
+    /// The theme names no preset, so the default theme is used.
+    UnknownTheme { name: String },
+}
+
+impl ConfigWarning {
+    /// Name of the offending key, without the path of its parent.
+    fn name(&self) -> &str {
+        match self.kind {
+            WarningKind::UnknownKey { .. } | WarningKind::Outdated { .. } | WarningKind::UnknownTheme { .. } => {
+                &self.key
+            }
+            // Chords contain no dots, so the last segment is the whole chord.
+            _ => self.key.rsplit('.').next().unwrap_or(&self.key),
+        }
+    }
+
+    fn message(&self) -> String {
+        match &self.kind {
+            WarningKind::UnknownKey { .. } => format!("unknown key `{}` is ignored", self.name()),
+            WarningKind::InvalidChord(e) => format!("keybind `{}` is ignored: {}", self.name(), e),
+            WarningKind::DuplicateChord { other } => {
+                format!("keybind `{}` is the same chord as `{}`", self.name(), other)
+            }
+            WarningKind::ShadowedChord { by } => format!("keybind `{}` is shadowed by `{}`", self.name(), by),
+            WarningKind::Outdated { version } => {
+                format!("config is at version {}, upgraded to version {} while loading", version, CONFIG_VERSION)
+            }
+            WarningKind::UnknownTheme { name } => {
+                format!("unknown theme `{}`, using `{}` instead", name, Theme::default())
+            }
+        }
+    }
+
+    fn help(&self) -> Option<String> {
+        match &self.kind {
+            WarningKind::UnknownKey { suggestion } => suggestion.as_ref().map(|s| format!("did you mean `{}`?", s)),
+            WarningKind::InvalidChord(e) => e.suggestion().map(|s| format!("did you mean `{}`?", s)),
+            WarningKind::DuplicateChord { .. } => Some("only one of them takes effect".to_string()),
+            WarningKind::ShadowedChord { by } => Some(format!("`{}` also fires whenever this chord is pressed", by)),
+            WarningKind::Outdated { .. } => {
+                Some("run `aim2go config migrate <game>` to upgrade the file, keeping a backup".to_string())
+            }
+            WarningKind::UnknownTheme { name } => {
+                let names = PRESETS.iter().map(|(preset, _)| *preset);
+                Some(match closest_key(name, names.clone()) {
+                    Some(preset) => format!("did you mean `{}`?", preset),
+                    None => format!("expected one of {}", names.collect::<Vec<_>>().join(", ")),
+                })
+            }
+        }
+    }
+
+    /// One-line form of the warning, for logs.
+    pub fn summary(&self) -> String {
+        match self.help() {
+            Some(help) => format!("{} ({})", self.message(), help),
+            None => self.message(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "warning: {}", self.message())?;
+        match self.line {
+            Some(line) => write!(f, "  --> {}:{}", self.path.display(), line)?,
+            None => write!(f, "  --> {}", self.path.display())?,
+        }
+        if let Some(help) = self.help() {
+            write!(f, "\n   = help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the keys of `value` that do not exist in `known`, the serialized default config.
+pub(crate) fn unknown_keys(path: &Path, content: &str, value: &Value, known: &Value) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    collect_unknown_keys(path, content, "", value, known, &mut warnings);
+    warnings
+}
+
+fn collect_unknown_keys(
+    path: &Path,
+    content: &str,
+    prefix: &str,
+    value: &Value,
+    known: &Value,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    let (Some(mapping), Some(known_mapping)) = (value.as_mapping(), known.as_mapping()) else {
+        return;
+    };
+    if FREE_FORM_KEYS.contains(&prefix) {
+        return;
+    }
+
+    for (key, child) in mapping {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let full_key = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match known_mapping.get(key) {
+            Some(known_child) => collect_unknown_keys(path, content, &full_key, child, known_child, warnings),
+            None => warnings.push(ConfigWarning {
+                path: path.to_path_buf(),
+                line: find_key_line(content, key),
+                kind: WarningKind::UnknownKey {
+                    suggestion: closest_key(key, known_mapping.keys().filter_map(Value::as_str)),
+                },
+                key: full_key,
+            }),
+        }
+    }
+}
+
+/// Warns when the theme at `key`, either a name or the `preset` of a map, names no preset.
+pub(crate) fn theme_warning(path: &Path, content: &str, key: &str, theme: Option<&Value>) -> Option<ConfigWarning> {
+    let (key, name) = match theme? {
+        Value::String(name) => (key.to_string(), name.as_str()),
+        theme => (format!("{}.preset", key), theme.get("preset")?.as_str()?),
+    };
+    if Theme::preset(name).is_some() {
+        return None;
+    }
+    let line = find_key_line(content, key.rsplit('.').next().unwrap_or(&key));
+    Some(ConfigWarning {
+        path: path.to_path_buf(),
+        key,
+        line,
+        kind: WarningKind::UnknownTheme { name: name.to_string() },
+    })
+}
+
+/// Returns the first line declaring `key`; good enough to point the user at it.
+pub(super) fn find_key_line(content: &str, key: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|index| index + 1)
+}
+
+/// Picks the candidate closest to `key`, if it is plausibly a typo of it.
+pub(super) fn closest_key<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (key.len() / 3).max(2);
+    candidates
+        .map(|candidate| (edit_distance(&key.to_lowercase(), &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, Config};
+
+    fn path() -> PathBuf {
+        PathBuf::from("config.yaml")
+    }
+
+    fn known() -> Value {
+        serde_yaml::to_value(Config::default()).unwrap()
+    }
+
+    #[test]
+    fn misspelled_key_suggests_the_closest_one() {
+        let content = "game:\n  overlay:\n    layres: []\n";
+        let value: Value = serde_yaml::from_str(content).unwrap();
+        let warnings = unknown_keys(&path(), content, &value, &known());
+
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(warning.key, "game.overlay.layres");
+        assert_eq!(warning.line, Some(3));
+        assert!(matches!(&warning.kind, WarningKind::UnknownKey { suggestion: Some(s) } if s == "layers"));
+        assert_eq!(warning.summary(), "unknown key `game.overlay.layres` is ignored (did you mean `layers`?)");
+    }
+
+    #[test]
+    fn unrelated_key_gets_no_suggestion() {
+        assert_eq!(closest_key("volume", ["layers", "crosshair"].into_iter()), None);
+        assert_eq!(closest_key("Crosshiar", ["layers", "crosshair"].into_iter()), Some("crosshair".to_string()));
+    }
+
+    #[test]
+    fn free_form_maps_are_not_checked() {
+        let content = "game:\n  keybinds:\n    keyboard:\n      CTRL+Q: Quit\n";
+        let value: Value = serde_yaml::from_str(content).unwrap();
+        assert!(unknown_keys(&path(), content, &value, &known()).is_empty());
+    }
+
+    #[test]
+    fn syntax_error_points_at_its_line_and_column() {
+        let content = "version: 4\ngame:\n  overlay: [layers\n  keybinds: {}\n";
+        let error = parse_config(&path(), content).unwrap_err();
+
+        let ConfigError::Parse { location, snippet, .. } = &error else {
+            panic!("expected a parse error, got {:?}", error);
+        };
+        // The flow sequence opened on line 3 is only found unclosed on line 4.
+        assert_eq!(*location, Some((4, 11)));
+        assert_eq!(snippet.as_deref(), Some("  keybinds: {}"));
+        assert!(error.to_string().contains("--> config.yaml:4:11"));
+    }
+
+    #[test]
+    fn schema_error_names_the_key() {
+        let content = "version: 4\ngame:\n  overlay:\n    layers: []\n    crosshair:\n      size: big\n  keybinds:\n    keyboard: {}\n    mouse: {}\n";
+        let error = parse_config(&path(), content).unwrap_err();
+
+        let ConfigError::Parse { key, location, snippet, .. } = &error else {
+            panic!("expected a parse error, got {:?}", error);
+        };
+        assert_eq!(key.as_deref(), Some("game.overlay.crosshair.size"));
+        assert_eq!(location.map(|(line, _)| line), Some(6));
+        assert_eq!(snippet.as_deref(), Some("      size: big"));
+    }
+
+    fn config_with_theme(theme: &str) -> String {
+        let mut value = known();
+        value["app"] = serde_yaml::from_str(&format!("theme: {}", theme)).unwrap();
+        serde_yaml::to_string(&value).unwrap()
+    }
+
+    #[test]
+    fn unknown_theme_falls_back_to_the_default_with_a_warning() {
+        let content = config_with_theme("purple");
+        let loaded = parse_config(&path(), &content).unwrap();
+
+        assert_eq!(loaded.config.app.theme, Some(Theme::default()));
+        let warning = loaded
+            .warnings
+            .iter()
+            .find(|warning| matches!(&warning.kind, WarningKind::UnknownTheme { name } if name == "purple"))
+            .expect("no warning about the theme");
+        assert_eq!(warning.key, "app.theme");
+        assert!(warning.line.is_some());
+        assert!(warning.summary().starts_with("unknown theme `purple`, using `magenta` instead (expected one of"));
+    }
+
+    #[test]
+    fn unknown_theme_preset_suggests_the_closest_one() {
+        let content = config_with_theme("{ preset: gren, accent: '#ffd700' }");
+        let loaded = parse_config(&path(), &content).unwrap();
+
+        let warning = loaded
+            .warnings
+            .iter()
+            .find(|warning| matches!(warning.kind, WarningKind::UnknownTheme { .. }))
+            .expect("no warning about the theme");
+        assert_eq!(warning.key, "app.theme.preset");
+        assert!(warning.summary().ends_with("(did you mean `green`?)"));
+    }
+
+    #[test]
+    fn known_themes_raise_no_warning() {
+        for theme in ["Blue", "{ preset: red, primary: '#102030' }", "{ primary: '#102030' }"] {
+            let content = config_with_theme(theme);
+            let loaded = parse_config(&path(), &content).unwrap();
+            assert!(
+                !loaded.warnings.iter().any(|warning| matches!(warning.kind, WarningKind::UnknownTheme { .. })),
+                "{}",
+                theme
+            );
+        }
+    }
+}
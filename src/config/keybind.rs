@@ -0,0 +1,585 @@
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::error::{closest_key, find_key_line, ConfigWarning, WarningKind};
+use super::{KeybindConfig, TuiCommand};
+
+/// Canonical modifier names, in the order they are written.
+const MODIFIER_NAMES: [&str; 4] = ["CTRL", "SHIFT", "ALT", "WIN"];
+
+/// Accepted spellings of each modifier, indexed like `MODIFIER_NAMES`.
+const MODIFIER_ALIASES: [(&str, usize); 7] = [
+    ("CONTROL", 0),
+    ("OPTION", 2),
+    ("SUPER", 3),
+    ("META", 3),
+    ("CMD", 3),
+    ("COMMAND", 3),
+    ("WINDOWS", 3),
+];
+
+/// Non-character keys, canonical spelling first.
+const NAMED_KEYS: [(&str, Key); 29] = [
+    ("ESC", Key::Esc),
+    ("ESCAPE", Key::Esc),
+    ("ENTER", Key::Enter),
+    ("RETURN", Key::Enter),
+    ("TAB", Key::Tab),
+    ("SPACE", Key::Space),
+    ("BACKSPACE", Key::Backspace),
+    ("DELETE", Key::Delete),
+    ("DEL", Key::Delete),
+    ("INSERT", Key::Insert),
+    ("INS", Key::Insert),
+    ("HOME", Key::Home),
+    ("END", Key::End),
+    ("PAGEUP", Key::PageUp),
+    ("PGUP", Key::PageUp),
+    ("PAGEDOWN", Key::PageDown),
+    ("PGDN", Key::PageDown),
+    ("UP", Key::Up),
+    ("DOWN", Key::Down),
+    ("LEFT", Key::Left),
+    ("RIGHT", Key::Right),
+    ("CAPSLOCK", Key::CapsLock),
+    ("CAPS", Key::CapsLock),
+    ("PRINTSCREEN", Key::PrintScreen),
+    ("PRTSC", Key::PrintScreen),
+    ("PAUSE", Key::Pause),
+    ("NUMLOCK", Key::NumLock),
+    ("SCROLLLOCK", Key::ScrollLock),
+    ("MENU", Key::Menu),
+];
+
+/// Mouse inputs, canonical spelling first.
+const MOUSE_INPUTS: [(&str, MouseInput); 14] = [
+    ("LMB", MouseInput::Left),
+    ("MOUSE1", MouseInput::Left),
+    ("RMB", MouseInput::Right),
+    ("MOUSE2", MouseInput::Right),
+    ("MMB", MouseInput::Middle),
+    ("MOUSE3", MouseInput::Middle),
+    ("MB4", MouseInput::Back),
+    ("BACK", MouseInput::Back),
+    ("MB5", MouseInput::Forward),
+    ("FORWARD", MouseInput::Forward),
+    ("WHEELUP", MouseInput::WheelUp),
+    ("SCROLLUP", MouseInput::WheelUp),
+    ("WHEELDOWN", MouseInput::WheelDown),
+    ("SCROLLDOWN", MouseInput::WheelDown),
+];
+
+/// Characters that may be bound on their own; `+` separates the parts of a chord.
+pub const PUNCTUATION: &str = "-=[];',./\\`";
+
+/// Which physical key of a modifier pair a chord asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Either,
+    Left,
+    Right,
+}
+
+/// A modifier key held as part of a chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Win,
+}
+
+/// The set of modifiers held for a chord.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers([Option<Side>; 4]);
+
+impl Modifiers {
+    /// Returns which side of `modifier` must be held, if it must be held at all.
+    pub fn get(&self, modifier: Modifier) -> Option<Side> {
+        self.0[modifier as usize]
+    }
+
+    /// Returns these modifiers with `modifier` held on `side`.
+    pub fn with(mut self, modifier: Modifier, side: Side) -> Self {
+        self.0[modifier as usize] = Some(side);
+        self
+    }
+
+    /// Returns these modifiers without `modifier`.
+    pub fn without(mut self, modifier: Modifier) -> Self {
+        self.0[modifier as usize] = None;
+        self
+    }
+
+    /// Does holding `other` also satisfy `self`? `CTRL` covers `LCTRL` but not the reverse.
+    pub fn covers(&self, other: &Modifiers) -> bool {
+        self.0.iter().zip(other.0.iter()).all(|pair| match pair {
+            (None, None) | (Some(Side::Either), Some(_)) => true,
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        })
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (side, name) in self.0.iter().zip(MODIFIER_NAMES) {
+            match side {
+                Some(Side::Either) => write!(f, "{}+", name)?,
+                Some(Side::Left) => write!(f, "L{}+", name)?,
+                Some(Side::Right) => write!(f, "R{}+", name)?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A non-modifier keyboard key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A letter, digit or punctuation key; letters are stored upper-case.
+    Char(char),
+    /// `F1` to `F24`.
+    Function(u8),
+    Esc,
+    Enter,
+    Tab,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    CapsLock,
+    PrintScreen,
+    Pause,
+    NumLock,
+    ScrollLock,
+    Menu,
+}
+
+impl Key {
+    fn parse(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c.is_ascii_alphanumeric() || PUNCTUATION.contains(c) {
+                return Some(Key::Char(c.to_ascii_uppercase()));
+            }
+        }
+        if let Some(number) = name.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+            if (1..=24).contains(&number) {
+                return Some(Key::Function(number));
+            }
+        }
+        NAMED_KEYS.iter().find(|(alias, _)| *alias == name).map(|(_, key)| *key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Function(number) => write!(f, "F{}", number),
+            key => {
+                let name = NAMED_KEYS.iter().find(|(_, named)| named == key).map_or("?", |(name, _)| *name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+/// A mouse button or wheel direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseInput {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    WheelUp,
+    WheelDown,
+}
+
+impl MouseInput {
+    fn parse(name: &str) -> Option<MouseInput> {
+        MOUSE_INPUTS.iter().find(|(alias, _)| *alias == name).map(|(_, input)| *input)
+    }
+}
+
+impl fmt::Display for MouseInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = MOUSE_INPUTS.iter().find(|(_, input)| input == self).map_or("?", |(name, _)| *name);
+        f.write_str(name)
+    }
+}
+
+/// A keyboard binding such as `CTRL+SHIFT+ESC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl KeyChord {
+    /// Would pressing `other` also trigger `self`?
+    pub fn shadows(&self, other: &KeyChord) -> bool {
+        self != other && self.key == other.key && self.modifiers.covers(&other.modifiers)
+    }
+}
+
+/// A mouse binding such as `LMB` or `CTRL+WHEELUP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseChord {
+    pub modifiers: Modifiers,
+    pub input: MouseInput,
+}
+
+impl MouseChord {
+    /// Would pressing `other` also trigger `self`?
+    pub fn shadows(&self, other: &MouseChord) -> bool {
+        self != other && self.input == other.input && self.modifiers.covers(&other.modifiers)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = ChordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names = NAMED_KEYS.iter().map(|(name, _)| *name);
+        let (modifiers, key) = parse_chord(s, Key::parse, names)?;
+        Ok(KeyChord { modifiers, key })
+    }
+}
+
+impl FromStr for MouseChord {
+    type Err = ChordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names = MOUSE_INPUTS.iter().map(|(name, _)| *name);
+        let (modifiers, input) = parse_chord(s, MouseInput::parse, names)?;
+        Ok(MouseChord { modifiers, input })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.modifiers, self.key)
+    }
+}
+
+impl fmt::Display for MouseChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.modifiers, self.input)
+    }
+}
+
+impl Serialize for KeyChord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyChord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for MouseChord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MouseChord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Reasons a chord string can't be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChordError {
+    /// The chord is empty or has an empty part, as in `CTRL++Q`.
+    Malformed(String),
+    /// The last part of the chord is not a known key or button.
+    UnknownKey { name: String, suggestion: Option<String> },
+    /// A part before the last one is not a modifier.
+    UnknownModifier { name: String, suggestion: Option<String> },
+    /// The same modifier is listed twice, as in `CTRL+LCTRL+Q`.
+    DuplicateModifier(String),
+}
+
+impl ChordError {
+    /// Closest valid name to the unknown part, if there is one.
+    pub fn suggestion(&self) -> Option<&str> {
+        match self {
+            ChordError::UnknownKey { suggestion, .. } | ChordError::UnknownModifier { suggestion, .. } => {
+                suggestion.as_deref()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChordError::Malformed(chord) => write!(f, "`{}` is not a valid chord", chord),
+            ChordError::UnknownKey { name, .. } => write!(f, "unknown key `{}`", name),
+            ChordError::UnknownModifier { name, .. } => write!(f, "`{}` is not a modifier", name),
+            ChordError::DuplicateModifier(name) => write!(f, "modifier `{}` is listed twice", name),
+        }
+    }
+}
+
+impl std::error::Error for ChordError {}
+
+/// Splits a chord on `+`, parsing every part but the last as a modifier.
+fn parse_chord<'a, T>(
+    chord: &str,
+    parse_last: fn(&str) -> Option<T>,
+    names: impl Iterator<Item = &'a str>,
+) -> Result<(Modifiers, T), ChordError> {
+    let parts: Vec<String> = chord.split('+').map(|part| part.trim().to_uppercase()).collect();
+    if parts.iter().any(String::is_empty) {
+        return Err(ChordError::Malformed(chord.trim().to_string()));
+    }
+
+    let (last, modifier_parts) = parts.split_last().expect("split always yields a part");
+    let mut modifiers = Modifiers::default();
+    for part in modifier_parts {
+        let (index, side) = parse_modifier(part).ok_or_else(|| ChordError::UnknownModifier {
+            name: part.clone(),
+            suggestion: closest_key(part, MODIFIER_NAMES.into_iter()),
+        })?;
+        if modifiers.0[index].replace(side).is_some() {
+            return Err(ChordError::DuplicateModifier(MODIFIER_NAMES[index].to_string()));
+        }
+    }
+
+    let input = parse_last(last).ok_or_else(|| ChordError::UnknownKey {
+        name: last.clone(),
+        suggestion: closest_key(last, names),
+    })?;
+    Ok((modifiers, input))
+}
+
+/// Parses `CTRL`, `LCTRL`, `RCTRL` and friends into a modifier index and side.
+fn parse_modifier(part: &str) -> Option<(usize, Side)> {
+    let lookup = |name: &str| {
+        MODIFIER_NAMES
+            .iter()
+            .position(|modifier| *modifier == name)
+            .or_else(|| MODIFIER_ALIASES.iter().find(|(alias, _)| *alias == name).map(|(_, index)| *index))
+    };
+
+    if let Some(index) = lookup(part) {
+        return Some((index, Side::Either));
+    }
+    if let Some(rest) = part.strip_prefix('L') {
+        return lookup(rest).map(|index| (index, Side::Left));
+    }
+    if let Some(rest) = part.strip_prefix('R') {
+        return lookup(rest).map(|index| (index, Side::Right));
+    }
+    None
+}
+
+/// Common interface of keyboard and mouse chords for conflict checks.
+trait Chord: FromStr<Err = ChordError> + fmt::Display + PartialEq {
+    fn shadows(&self, other: &Self) -> bool;
+}
+
+impl Chord for KeyChord {
+    fn shadows(&self, other: &Self) -> bool {
+        KeyChord::shadows(self, other)
+    }
+}
+
+impl Chord for MouseChord {
+    fn shadows(&self, other: &Self) -> bool {
+        MouseChord::shadows(self, other)
+    }
+}
+
+/// Reports chords in `keybinds` that can't be parsed, repeat another chord or are shadowed by one.
+pub(crate) fn chord_warnings(path: &Path, content: &str, keybinds: &KeybindConfig) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    check_map::<KeyChord, _>(path, content, "game.keybinds.keyboard", &keybinds.keyboard, &mut warnings);
+    check_map::<MouseChord, _>(path, content, "game.keybinds.mouse", &keybinds.mouse, &mut warnings);
+    warnings
+}
+
+/// Reports problems with the chords of a `tui_keys` map found under `prefix`.
+pub(crate) fn tui_key_warnings(
+    path: &Path,
+    content: &str,
+    prefix: &str,
+    tui_keys: &HashMap<String, TuiCommand>,
+) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    check_map::<KeyChord, _>(path, content, prefix, tui_keys, &mut warnings);
+    warnings
+}
+
+fn check_map<C: Chord, V>(
+    path: &Path,
+    content: &str,
+    prefix: &str,
+    map: &HashMap<String, V>,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    let mut raw: Vec<&String> = map.keys().collect();
+    raw.sort();
+
+    let mut warn = |raw: &str, kind: WarningKind| {
+        warnings.push(ConfigWarning {
+            path: path.to_path_buf(),
+            key: format!("{}.{}", prefix, raw),
+            line: find_key_line(content, raw),
+            kind,
+        })
+    };
+
+    let mut parsed: Vec<(&str, C)> = Vec::new();
+    for raw in raw {
+        match raw.parse::<C>() {
+            Ok(chord) => parsed.push((raw, chord)),
+            Err(e) => warn(raw, WarningKind::InvalidChord(e)),
+        }
+    }
+
+    for (index, (raw, chord)) in parsed.iter().enumerate() {
+        if let Some((other, _)) = parsed[..index].iter().find(|(_, earlier)| earlier == chord) {
+            warn(raw, WarningKind::DuplicateChord { other: other.to_string() });
+        } else if let Some((other, _)) = parsed.iter().find(|(_, candidate)| candidate.shadows(chord)) {
+            warn(raw, WarningKind::ShadowedChord { by: other.to_string() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Actions;
+
+    fn key(chord: &str) -> KeyChord {
+        chord.parse().unwrap_or_else(|e| panic!("`{}` does not parse: {}", chord, e))
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let chord = key("ctrl + shift+ esc");
+        assert_eq!(chord.key, Key::Esc);
+        assert_eq!(chord.modifiers.get(Modifier::Ctrl), Some(Side::Either));
+        assert_eq!(chord.modifiers.get(Modifier::Shift), Some(Side::Either));
+        assert_eq!(chord.modifiers.get(Modifier::Alt), None);
+        assert_eq!(chord.to_string(), "CTRL+SHIFT+ESC");
+
+        assert_eq!(key("q").key, Key::Char('Q'));
+        assert_eq!(key("F12").key, Key::Function(12));
+        assert_eq!(key("/").key, Key::Char('/'));
+    }
+
+    #[test]
+    fn parses_sided_modifiers() {
+        let chord = key("LCTRL+RALT+Q");
+        assert_eq!(chord.modifiers.get(Modifier::Ctrl), Some(Side::Left));
+        assert_eq!(chord.modifiers.get(Modifier::Alt), Some(Side::Right));
+        assert_eq!(chord.to_string(), "LCTRL+RALT+Q");
+    }
+
+    #[test]
+    fn aliases_are_written_canonically() {
+        assert_eq!(key("control+option+cmd+escape").to_string(), "CTRL+ALT+WIN+ESC");
+        assert_eq!(key("LCONTROL+PgUp").to_string(), "LCTRL+PAGEUP");
+        assert_eq!(key("SUPER+Q"), key("WIN+Q"));
+
+        let mouse: MouseChord = "shift+mouse1".parse().unwrap();
+        assert_eq!(mouse.to_string(), "SHIFT+LMB");
+    }
+
+    #[test]
+    fn rejects_duplicate_modifiers() {
+        assert_eq!("CTRL+LCTRL+Q".parse::<KeyChord>(), Err(ChordError::DuplicateModifier("CTRL".to_string())));
+        assert_eq!("META+WIN+Q".parse::<KeyChord>(), Err(ChordError::DuplicateModifier("WIN".to_string())));
+    }
+
+    #[test]
+    fn rejects_empty_parts() {
+        for chord in ["", "CTRL++Q", "CTRL+", "+Q", " + "] {
+            assert!(
+                matches!(chord.parse::<KeyChord>(), Err(ChordError::Malformed(_))),
+                "`{}` should be malformed",
+                chord
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_names_with_suggestions() {
+        let Err(ChordError::UnknownModifier { name, suggestion }) = "CTLR+Q".parse::<KeyChord>() else {
+            panic!("expected an unknown modifier");
+        };
+        assert_eq!((name.as_str(), suggestion.as_deref()), ("CTLR", Some("CTRL")));
+
+        let Err(ChordError::UnknownKey { name, suggestion }) = "CTRL+ESCC".parse::<KeyChord>() else {
+            panic!("expected an unknown key");
+        };
+        assert_eq!((name.as_str(), suggestion.as_deref()), ("ESCC", Some("ESC")));
+
+        assert!(matches!("F25".parse::<KeyChord>(), Err(ChordError::UnknownKey { .. })));
+        assert!(matches!("CTRL+LMB".parse::<KeyChord>(), Err(ChordError::UnknownKey { .. })));
+    }
+
+    #[test]
+    fn rejects_non_ascii_input_without_panicking() {
+        assert!(matches!("é+Q".parse::<KeyChord>(), Err(ChordError::UnknownModifier { .. })));
+        assert!(matches!("Ĺ+Q".parse::<KeyChord>(), Err(ChordError::UnknownModifier { .. })));
+        assert!(matches!("CTRL+é".parse::<KeyChord>(), Err(ChordError::UnknownKey { .. })));
+        assert!(matches!("ß+LMB".parse::<MouseChord>(), Err(ChordError::UnknownModifier { .. })));
+    }
+
+    #[test]
+    fn general_chords_shadow_specific_ones() {
+        assert!(key("CTRL+Q").shadows(&key("LCTRL+Q")));
+        assert!(!key("LCTRL+Q").shadows(&key("CTRL+Q")));
+        assert!(!key("LCTRL+Q").shadows(&key("RCTRL+Q")));
+        // Extra or missing modifiers make a different chord, not a shadowed one.
+        assert!(!key("CTRL+Q").shadows(&key("CTRL+SHIFT+Q")));
+        assert!(!key("CTRL+Q").shadows(&key("CTRL+W")));
+        assert!(!key("CTRL+Q").shadows(&key("control+q")));
+    }
+
+    #[test]
+    fn warns_about_invalid_duplicate_and_shadowed_chords() {
+        let content = "game:\n  keybinds:\n    keyboard:\n      CTRL+Q: Quit\n      control+q: Quit\n      LCTRL+W: Quit\n      CTRL+W: Quit\n      CTLR+E: Quit\n";
+        let keybinds = KeybindConfig {
+            keyboard: ["CTRL+Q", "control+q", "LCTRL+W", "CTRL+W", "CTLR+E"]
+                .into_iter()
+                .map(|chord| (chord.to_string(), Actions::SnapAim))
+                .collect(),
+            mouse: HashMap::new(),
+        };
+        let warnings = chord_warnings(Path::new("config.yaml"), content, &keybinds);
+        let summary: Vec<(&str, &WarningKind)> = warnings.iter().map(|w| (w.key.as_str(), &w.kind)).collect();
+
+        assert_eq!(summary.len(), 3, "{:?}", summary);
+        assert!(matches!(summary[0], ("game.keybinds.keyboard.CTLR+E", WarningKind::InvalidChord(_))));
+        assert!(matches!(summary[1], ("game.keybinds.keyboard.LCTRL+W", WarningKind::ShadowedChord { by }) if by == "CTRL+W"));
+        assert!(matches!(summary[2], ("game.keybinds.keyboard.control+q", WarningKind::DuplicateChord { other }) if other == "CTRL+Q"));
+        assert_eq!(warnings[0].line, Some(8));
+    }
+}
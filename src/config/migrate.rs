@@ -0,0 +1,347 @@
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+use std::path::PathBuf;
+
+use super::{AppConfig, CrosshairConfig, OverlayLayer, CONFIG_VERSION};
+
+/// A single migration step; it upgrades a config mapping by exactly one version
+/// and records a human readable line for every change it makes.
+type Step = fn(&mut Mapping, &mut Vec<String>);
+
+/// Migration steps, where `MIGRATIONS[n]` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [Step; CONFIG_VERSION as usize] = [v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4];
+
+/// Outcome of migrating a config to the current version.
+#[derive(Debug, Default)]
+pub struct Migration {
+    /// Version the config was written in.
+    pub from: u32,
+    /// Version the config was migrated to.
+    pub to: u32,
+    /// Description of every change made, in order.
+    pub changes: Vec<String>,
+    /// Copy of the original file, if it was rewritten on disk.
+    pub backup: Option<PathBuf>,
+}
+
+impl Migration {
+    /// Checks if any migration step ran.
+    pub fn is_needed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// Reasons a config cannot be migrated.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The document is not a YAML mapping.
+    NotAMapping,
+    /// The `version` key is not a non-negative integer.
+    InvalidVersion(Value),
+    /// The config was written by a newer aim2go.
+    Unsupported(u32),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::NotAMapping => write!(f, "config is not a YAML mapping"),
+            MigrationError::InvalidVersion(value) => {
+                write!(f, "config `version` must be a non-negative integer, found {:?}", value)
+            }
+            MigrationError::Unsupported(version) => write!(
+                f,
+                "config version {} is newer than the latest supported version {}",
+                version, CONFIG_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Returns the schema version of a raw config; a missing `version` key means version 0.
+pub fn config_version(value: &Value) -> Result<u32, MigrationError> {
+    let mapping = value.as_mapping().ok_or(MigrationError::NotAMapping)?;
+    match mapping.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| MigrationError::InvalidVersion(version.clone())),
+    }
+}
+
+/// Upgrades a raw config to [`CONFIG_VERSION`] in place.
+pub fn migrate(value: &mut Value) -> Result<Migration, MigrationError> {
+    let from = config_version(value)?;
+    if from > CONFIG_VERSION {
+        return Err(MigrationError::Unsupported(from));
+    }
+
+    let mapping = value.as_mapping_mut().ok_or(MigrationError::NotAMapping)?;
+    let mut changes = Vec::new();
+
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(mapping, &mut changes);
+        mapping.insert("version".into(), (version as u64 + 1).into());
+    }
+
+    Ok(Migration {
+        from,
+        to: CONFIG_VERSION,
+        changes,
+        backup: None,
+    })
+}
+
+/// v0 -> v1: configs gain an explicit `version` key.
+fn v0_to_v1(_config: &mut Mapping, changes: &mut Vec<String>) {
+    changes.push("added `version` key".to_string());
+}
+
+/// v1 -> v2: app settings moved to the global `app.yaml`; the `app` section stays as
+/// per-game overrides of it.
+///
+/// Every value is kept, even one equal to the built-in default: `app.yaml` may hold
+/// something else, and dropping the value would change the game's settings.
+fn v1_to_v2(config: &mut Mapping, changes: &mut Vec<String>) {
+    let Some(Value::Mapping(app)) = config.get_mut("app") else {
+        return;
+    };
+    let defaults = serde_yaml::to_value(AppConfig::default()).unwrap_or_default();
+
+    // Unknown keys are left alone too; loading the config warns about them.
+    for name in app.keys().filter_map(Value::as_str).filter(|name| defaults.get(name).is_some()) {
+        changes.push(format!(
+            "kept `app.{}` as a per-game override of app.yaml; remove it to use the value from app.yaml",
+            name
+        ));
+    }
+
+    if app.is_empty() {
+        config.remove("app");
+    }
+}
+
+/// v2 -> v3: the `game.overlay.render` list of layer names became `game.overlay.layers`,
+/// where every layer has its own z-order, opacity and anchor.
+fn v2_to_v3(config: &mut Mapping, changes: &mut Vec<String>) {
+    let Some(Value::Mapping(overlay)) = config
+        .get_mut("game")
+        .and_then(|game| game.get_mut("overlay"))
+    else {
+        return;
+    };
+    let Some(Value::Sequence(render)) = overlay.remove("render") else {
+        return;
+    };
+
+    let layers: Vec<Value> = render
+        .into_iter()
+        .map(|name| match serde_yaml::from_value::<OverlayLayer>(name.clone()) {
+            Ok(layer) => serde_yaml::to_value(layer.default_config()).unwrap_or_default(),
+            // Unknown names are kept so loading the config reports them.
+            Err(_) => Value::Mapping(Mapping::from_iter([("layer".into(), name)])),
+        })
+        .collect();
+    overlay.insert("layers".into(), Value::Sequence(layers));
+    changes.push(
+        "replaced `game.overlay.render` with `game.overlay.layers`, using the default z-order, opacity and anchor"
+            .to_string(),
+    );
+}
+
+/// Smallest `crosshair_size` carried over by [`v3_to_v4`]; a crosshair of size 0 draws nothing.
+const MIN_CROSSHAIR_SIZE: f64 = 1.0;
+
+/// v3 -> v4: `game.overlay.crosshair_type` and `crosshair_size` moved into the
+/// `game.overlay.crosshair` section, next to the new crosshair settings.
+fn v3_to_v4(config: &mut Mapping, changes: &mut Vec<String>) {
+    let Some(Value::Mapping(overlay)) = config
+        .get_mut("game")
+        .and_then(|game| game.get_mut("overlay"))
+    else {
+        return;
+    };
+    let crosshair_type = overlay.remove("crosshair_type");
+    let size = overlay.remove("crosshair_size");
+    if crosshair_type.is_none() && size.is_none() {
+        return;
+    }
+
+    // Without a gap or outline the crosshair looks as it did: a cross's arms were
+    // `crosshair_size` long from the centre, drawn 2 pixels wide.
+    let Ok(Value::Mapping(mut crosshair)) = serde_yaml::to_value(CrosshairConfig {
+        gap: 0.0,
+        outline_width: 0.0,
+        ..CrosshairConfig::default()
+    }) else {
+        return;
+    };
+    if let Some(crosshair_type) = crosshair_type {
+        crosshair.insert("type".into(), crosshair_type);
+    }
+    if let Some(mut size) = size {
+        // Older versions drew any size, but `size` and `arm_length` are now checked.
+        if let Some(value) = size.as_f64() {
+            let clamped = value.max(MIN_CROSSHAIR_SIZE).min(CrosshairConfig::MAX_LENGTH as f64);
+            if clamped != value {
+                changes.push(format!(
+                    "changed `game.overlay.crosshair_size` from {} to {}, the closest size a crosshair can have",
+                    value, clamped
+                ));
+                size = clamped.into();
+            }
+        }
+        crosshair.insert("size".into(), size.clone());
+        crosshair.insert("arm_length".into(), size);
+    }
+    overlay.insert("crosshair".into(), Value::Mapping(crosshair));
+    changes.push(
+        "moved `game.overlay.crosshair_type` and `crosshair_size` to `game.overlay.crosshair`, \
+         without a gap or outline"
+            .to_string(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, CrosshairType};
+
+    /// A config as written before versioning, with every key a migration touches.
+    const V0: &str = "\
+app:
+  tick_rate: 8
+  theme: green
+game:
+  overlay:
+    render:
+    - HUD
+    - Crosshair
+    crosshair_type: Cross
+    crosshair_size: 5
+  keybinds:
+    keyboard:
+      CTRL+Q: ClassCaptureWheel
+    mouse:
+      RMB: SnapAim
+";
+
+    /// Runs the steps up to `version` on the v0 config, as an older aim2go would have left it.
+    fn config_at(version: u32) -> Value {
+        let mut value: Value = serde_yaml::from_str(V0).unwrap();
+        let mapping = value.as_mapping_mut().unwrap();
+        for step in &MIGRATIONS[..version as usize] {
+            step(mapping, &mut Vec::new());
+        }
+        if version > 0 {
+            mapping.insert("version".into(), version.into());
+        }
+        value
+    }
+
+    #[test]
+    fn every_version_migrates_to_the_current_one() {
+        for version in 0..=CONFIG_VERSION {
+            let mut value = config_at(version);
+            let migration = migrate(&mut value).unwrap();
+
+            assert_eq!((migration.from, migration.to), (version, CONFIG_VERSION));
+            assert_eq!(migration.is_needed(), version < CONFIG_VERSION);
+            assert_eq!(config_version(&value).unwrap(), CONFIG_VERSION);
+            let config: Config = serde_yaml::from_value(value)
+                .unwrap_or_else(|e| panic!("config migrated from version {} does not load: {}", version, e));
+            assert_eq!(config.version, CONFIG_VERSION);
+        }
+    }
+
+    #[test]
+    fn migrating_twice_changes_nothing() {
+        let mut value = config_at(0);
+        let first = migrate(&mut value).unwrap();
+        assert!(first.changes.len() >= MIGRATIONS.len());
+
+        let migrated = value.clone();
+        let second = migrate(&mut value).unwrap();
+        assert!(!second.is_needed());
+        assert!(second.changes.is_empty());
+        assert_eq!(value, migrated);
+    }
+
+    #[test]
+    fn app_settings_are_kept_even_when_they_equal_the_defaults() {
+        let defaults = AppConfig::default();
+        let mut value = config_at(1);
+        let app = value.as_mapping_mut().unwrap().get_mut("app").unwrap().as_mapping_mut().unwrap();
+        app.insert("tick_rate".into(), defaults.tick_rate.into());
+
+        migrate(&mut value).unwrap();
+        let config: Config = serde_yaml::from_value(value).unwrap();
+        assert_eq!(config.app.tick_rate, Some(defaults.tick_rate));
+        assert!(config.app.theme.is_some());
+    }
+
+    /// A version 2 config with the given `crosshair_size`.
+    fn v2_with_size(size: &str) -> Value {
+        let config = format!(
+            "version: 2\ngame:\n  overlay:\n    render: [HUD, Crosshair, ESP]\n    crosshair_type: CrossWithDot\n    \
+             crosshair_size: {}\n  keybinds:\n    keyboard: {{}}\n    mouse: {{}}\n",
+            size
+        );
+        serde_yaml::from_str(&config).unwrap()
+    }
+
+    #[test]
+    fn v2_overlay_settings_carry_over_to_v4() {
+        let mut value = v2_with_size("7.5");
+        let migration = migrate(&mut value).unwrap();
+        assert_eq!(migration.changes.len(), 2);
+
+        let config: Config = serde_yaml::from_value(value).unwrap();
+        let layers: Vec<OverlayLayer> = config.game.overlay.layers.iter().map(|layer| layer.layer).collect();
+        assert_eq!(layers, [OverlayLayer::Hud, OverlayLayer::Crosshair, OverlayLayer::Esp]);
+        let crosshair = &config.game.overlay.crosshair;
+        assert_eq!(crosshair.crosshair_type, CrosshairType::CrossWithDot);
+        assert_eq!((crosshair.size, crosshair.arm_length), (7.5, 7.5));
+        assert_eq!((crosshair.gap, crosshair.outline_width), (0.0, 0.0));
+    }
+
+    #[test]
+    fn v2_crosshair_sizes_are_clamped_to_the_allowed_range() {
+        for (size, expected) in [("0", 1.0), ("-3", 1.0), ("250", CrosshairConfig::MAX_LENGTH)] {
+            let mut value = v2_with_size(size);
+            let migration = migrate(&mut value).unwrap();
+            assert!(
+                migration.changes.iter().any(|change| change.contains(&format!("from {} to {}", size, expected))),
+                "{:?}",
+                migration.changes
+            );
+
+            let config: Config = serde_yaml::from_value(value)
+                .unwrap_or_else(|e| panic!("config with crosshair_size {} does not load: {}", size, e));
+            let crosshair = &config.game.overlay.crosshair;
+            assert_eq!((crosshair.size, crosshair.arm_length), (expected, expected));
+        }
+    }
+
+    #[test]
+    fn missing_version_means_version_0() {
+        assert_eq!(config_version(&config_at(0)).unwrap(), 0);
+    }
+
+    #[test]
+    fn newer_and_invalid_versions_are_rejected() {
+        let mut newer = config_at(CONFIG_VERSION);
+        newer.as_mapping_mut().unwrap().insert("version".into(), (CONFIG_VERSION + 1).into());
+        assert!(matches!(migrate(&mut newer), Err(MigrationError::Unsupported(v)) if v == CONFIG_VERSION + 1));
+
+        let mut invalid = config_at(CONFIG_VERSION);
+        invalid.as_mapping_mut().unwrap().insert("version".into(), "two".into());
+        assert!(matches!(migrate(&mut invalid), Err(MigrationError::InvalidVersion(_))));
+
+        let mut sequence = Value::Sequence(Vec::new());
+        assert!(matches!(migrate(&mut sequence), Err(MigrationError::NotAMapping)));
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+
+use super::{get_app_config_file, get_game_config_file, resolve_game_config_with, ConfigError, ConfigWarning, EffectiveConfig};
+
+/// How often the watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Outcome of a change to a watched config file.
+#[derive(Debug)]
+pub enum ConfigReload {
+    /// The files validated and a new snapshot was published, with the warnings raised
+    /// while loading them.
+    Applied(Arc<EffectiveConfig>, Vec<ConfigWarning>),
+    /// The files failed to validate; the last good snapshot stays in effect.
+    Rejected(ConfigError),
+}
+
+/// Watches a game's `config.yaml` and the global `app.yaml`.
+///
+/// Whenever either file changes it is revalidated; valid edits are published as a new
+/// snapshot to every subscriber, invalid ones are reported and otherwise ignored.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    snapshot: watch::Receiver<Arc<EffectiveConfig>>,
+    /// Wakes the task to reload even if the files look unchanged.
+    force: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl ConfigWatcher {
+    /// Starts watching `game`'s config, with `initial` as the current snapshot.
+    ///
+    /// `on_reload` is called from the watcher task with the outcome of every change.
+    pub fn spawn(game: &str, initial: EffectiveConfig, on_reload: impl Fn(ConfigReload) + Send + 'static) -> Self {
+        Self::spawn_with(game, get_app_config_file(), initial, on_reload)
+    }
+
+    /// Starts watching like [`ConfigWatcher::spawn`], with the app settings in `app_file`.
+    fn spawn_with(
+        game: &str,
+        app_file: PathBuf,
+        initial: EffectiveConfig,
+        on_reload: impl Fn(ConfigReload) + Send + 'static,
+    ) -> Self {
+        let (snapshot_sender, snapshot) = watch::channel(Arc::new(initial));
+        let game = game.to_string();
+        let files = [get_game_config_file(&game), app_file];
+        let force = Arc::new(Notify::new());
+        let forced = Arc::clone(&force);
+        // Stamped before the task starts so edits made right after spawning are not missed.
+        let mut stamps = stamps_of(&files);
+        // `app.yaml` may be missing, in which case the defaults apply, but once it has
+        // been seen removing it is rejected like removing the game's config.
+        let mut required = [true, files[1].exists()];
+
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+            loop {
+                let forced = tokio::select! {
+                    _ = interval.tick() => false,
+                    _ = forced.notified() => true,
+                };
+
+                let current = stamps_of(&files);
+                if current == stamps && !forced {
+                    continue;
+                }
+                stamps = current;
+
+                let removed = files.iter().zip(required).find(|(file, required)| *required && !file.exists());
+                required[1] |= files[1].exists();
+
+                let reload = if let Some((file, _)) = removed {
+                    ConfigReload::Rejected(ConfigError::io(
+                        file,
+                        std::io::Error::new(std::io::ErrorKind::NotFound, "config file was removed"),
+                    ))
+                } else {
+                    match resolve_game_config_with(&files[1], &game) {
+                        Ok(resolved) => {
+                            let config = Arc::new(resolved.config);
+                            snapshot_sender.send_replace(Arc::clone(&config));
+                            ConfigReload::Applied(config, resolved.warnings)
+                        }
+                        Err(e) => ConfigReload::Rejected(e),
+                    }
+                };
+
+                on_reload(reload);
+            }
+        });
+
+        Self {
+            snapshot,
+            force,
+            task,
+        }
+    }
+
+    /// Revalidates the files right away, whether or not they changed.
+    ///
+    /// The outcome is passed to `on_reload` like any other reload.
+    pub fn reload(&self) {
+        self.force.notify_one();
+    }
+
+    /// Returns a receiver that always holds the latest valid snapshot.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<EffectiveConfig>> {
+        self.snapshot.clone()
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Modification time and size of a file, used to detect changes.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamps_of(files: &[PathBuf; 2]) -> [Stamp; 2] {
+    files.clone().map(|file| {
+        fs::metadata(file)
+            .ok()
+            .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::error::WarningKind;
+    use crate::config::tests::game_with;
+    use crate::config::{AppConfig, Config};
+    use tokio::sync::mpsc;
+
+    /// Watches a new game whose `app.yaml` sets `tick_rate: 10`, returning the reloads.
+    fn watched_game() -> (ConfigWatcher, String, PathBuf, mpsc::UnboundedReceiver<ConfigReload>) {
+        let game = game_with(&serde_yaml::to_string(&Config::default()).unwrap());
+        let app_file = get_game_config_file(&game).with_file_name("app.yaml");
+        fs::write(&app_file, "tick_rate: 10\n").unwrap();
+
+        let initial = EffectiveConfig::resolve(AppConfig { tick_rate: 10, ..AppConfig::default() }, Config::default());
+        let (sender, reloads) = mpsc::unbounded_channel();
+        let watcher = ConfigWatcher::spawn_with(&game, app_file.clone(), initial, move |reload| {
+            let _ = sender.send(reload);
+        });
+        (watcher, game, app_file, reloads)
+    }
+
+    /// Waits for the next reload, giving the watcher a few polls to notice a change.
+    async fn next_reload(reloads: &mut mpsc::UnboundedReceiver<ConfigReload>) -> ConfigReload {
+        tokio::time::timeout(POLL_INTERVAL * 4, reloads.recv())
+            .await
+            .expect("the watcher reloads")
+            .expect("the watcher is running")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn valid_edits_are_applied_with_their_warnings() {
+        let (watcher, _game, app_file, mut reloads) = watched_game();
+        let snapshot = watcher.subscribe();
+
+        fs::write(&app_file, "tick_rate: 20\ntick_rat: 30\n").unwrap();
+        let ConfigReload::Applied(config, warnings) = next_reload(&mut reloads).await else {
+            panic!("the edit is applied");
+        };
+        assert_eq!(config.app.tick_rate, 20);
+        assert_eq!(snapshot.borrow().app.tick_rate, 20);
+        assert!(matches!(&warnings[..], [warning] if warning.key == "tick_rat"
+            && matches!(warning.kind, WarningKind::UnknownKey { .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invalid_edits_are_rejected_and_the_last_good_config_kept() {
+        let (watcher, _game, app_file, mut reloads) = watched_game();
+        let snapshot = watcher.subscribe();
+
+        fs::write(&app_file, "tick_rate: 0\n").unwrap();
+        assert!(matches!(next_reload(&mut reloads).await, ConfigReload::Rejected(ConfigError::Parse { .. })));
+        assert_eq!(snapshot.borrow().app.tick_rate, 10);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn removing_the_game_config_is_rejected() {
+        let (watcher, game, _app_file, mut reloads) = watched_game();
+        let snapshot = watcher.subscribe();
+
+        fs::remove_file(get_game_config_file(&game)).unwrap();
+        assert!(matches!(next_reload(&mut reloads).await, ConfigReload::Rejected(ConfigError::Io { .. })));
+        assert_eq!(snapshot.borrow().app.tick_rate, 10);
+        assert!(!get_game_config_file(&game).exists());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn removing_the_app_config_is_rejected_instead_of_recreating_it() {
+        let (watcher, _game, app_file, mut reloads) = watched_game();
+        let snapshot = watcher.subscribe();
+
+        fs::remove_file(&app_file).unwrap();
+        assert!(matches!(next_reload(&mut reloads).await, ConfigReload::Rejected(ConfigError::Io { .. })));
+        watcher.reload();
+        assert!(matches!(next_reload(&mut reloads).await, ConfigReload::Rejected(ConfigError::Io { .. })));
+        assert_eq!(snapshot.borrow().app.tick_rate, 10);
+        assert!(!app_file.exists());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reload_rereads_unchanged_files() {
+        let (watcher, _game, _app_file, mut reloads) = watched_game();
+
+        // Unchanged files are left alone...
+        tokio::time::sleep(POLL_INTERVAL * 4).await;
+        assert!(reloads.try_recv().is_err());
+
+        // ...unless a reload is asked for.
+        watcher.reload();
+        let ConfigReload::Applied(config, _) = next_reload(&mut reloads).await else {
+            panic!("the files are reloaded");
+        };
+        assert_eq!(config.app.tick_rate, 10);
+    }
+}
@@ -0,0 +1,520 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::fmt;
+
+use crate::config::{CrosshairConfig, CrosshairPrimitive, CrosshairType};
+use crate::theme::Rgb;
+
+/// Start of every crosshair share code.
+pub const SHARE_CODE_PREFIX: &str = "A2G-";
+
+/// Format version written into share codes; codes with a higher one are rejected.
+pub const SHARE_CODE_VERSION: u8 = 1;
+
+/// A point in pixels, as `[x, y]` with `y` pointing down.
+pub type Point = [f32; 2];
+
+/// One of the shapes a crosshair is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// Filled circle
+    Disc { center: Point, radius: f32 },
+    /// Circle line `width` wide, centred on `radius`
+    Ring { center: Point, radius: f32, width: f32 },
+    /// Straight line `width` wide, with square ends at `from` and `to`
+    Segment { from: Point, to: Point, width: f32 },
+}
+
+/// Colour a shape is filled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    /// `outline_color`, opaque
+    Outline,
+    /// The crosshair colour at `fill_opacity`
+    Fill,
+}
+
+impl Shape {
+    /// Returns the border `width` wide around the shape, to draw its outline with.
+    ///
+    /// The border only surrounds the shape, so a translucent fill drawn over it does not
+    /// show the outline through.
+    pub fn outline(self, width: f32) -> Vec<Shape> {
+        match self {
+            Shape::Disc { center, radius } => vec![Shape::Ring {
+                center,
+                radius: radius + width / 2.0,
+                width,
+            }],
+            Shape::Ring { center, radius, width: ring_width } => {
+                let hole = radius - ring_width / 2.0;
+                let mut border = vec![Shape::Ring {
+                    center,
+                    radius: radius + ring_width / 2.0 + width / 2.0,
+                    width,
+                }];
+                if hole >= width {
+                    border.push(Shape::Ring {
+                        center,
+                        radius: hole - width / 2.0,
+                        width,
+                    });
+                } else if hole > 0.0 {
+                    // The hole is narrower than the outline, which fills all of it.
+                    border.push(Shape::Disc { center, radius: hole });
+                }
+                border
+            }
+            Shape::Segment { from, to, width: line_width } => {
+                if from == to {
+                    return Vec::new();
+                }
+                let (dx, dy) = direction(from, to);
+                let (nx, ny) = (-dy, dx);
+                let shift = |point: Point, along: f32, across: f32| {
+                    [point[0] + dx * along + nx * across, point[1] + dy * along + ny * across]
+                };
+                // The sides run the length of the outline; the ends fit between them.
+                let side = line_width / 2.0 + width / 2.0;
+                let end = line_width / 2.0;
+                vec![
+                    Shape::Segment { from: shift(from, -width, side), to: shift(to, width, side), width },
+                    Shape::Segment { from: shift(from, -width, -side), to: shift(to, width, -side), width },
+                    Shape::Segment { from: shift(from, -width / 2.0, end), to: shift(from, -width / 2.0, -end), width },
+                    Shape::Segment { from: shift(to, width / 2.0, -end), to: shift(to, width / 2.0, end), width },
+                ]
+            }
+        }
+    }
+}
+
+/// Returns the corners of a segment in drawing order, or `None` if it has no length.
+pub fn segment_corners(from: Point, to: Point, width: f32) -> Option<[Point; 4]> {
+    if from == to {
+        return None;
+    }
+    let (dx, dy) = direction(from, to);
+    // Perpendicular to the segment, half its width long.
+    let (nx, ny) = (-dy * width / 2.0, dx * width / 2.0);
+    Some([
+        [from[0] + nx, from[1] + ny],
+        [to[0] + nx, to[1] + ny],
+        [to[0] - nx, to[1] - ny],
+        [from[0] - nx, from[1] - ny],
+    ])
+}
+
+/// Unit vector from `from` to `to`; zero if they are the same point.
+fn direction(from: Point, to: Point) -> (f32, f32) {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (dx / length, dy / length)
+    }
+}
+
+/// Returns the shapes of a crosshair around `[0, 0]` moved by its `offset`, in the
+/// order they are drawn: every outline, then every fill.
+pub fn shapes(config: &CrosshairConfig) -> Vec<(Paint, Shape)> {
+    let fills = fill_shapes(config);
+    let mut shapes = Vec::with_capacity(fills.len() * 2);
+    if config.outline_width > 0.0 {
+        let outlines = fills.iter().flat_map(|shape| shape.outline(config.outline_width));
+        shapes.extend(outlines.map(|shape| (Paint::Outline, shape)));
+    }
+    shapes.extend(fills.into_iter().map(|shape| (Paint::Fill, shape)));
+    shapes
+}
+
+fn fill_shapes(config: &CrosshairConfig) -> Vec<Shape> {
+    let [x, y] = config.offset;
+    let at = |point: Point| [x + point[0], y + point[1]];
+    let mut shapes = Vec::new();
+
+    if config.crosshair_type.has_arms() {
+        // Right, left, down and up; a T has no top arm.
+        let mut directions = vec![(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0)];
+        if config.crosshair_type != CrosshairType::T {
+            directions.push((0.0, -1.0));
+        }
+        let (start, end) = (config.gap, config.gap + config.arm_length);
+        shapes.extend(directions.into_iter().map(|(dx, dy)| Shape::Segment {
+            from: at([dx * start, dy * start]),
+            to: at([dx * end, dy * end]),
+            width: config.thickness,
+        }));
+    }
+
+    match config.crosshair_type {
+        CrosshairType::Dot | CrosshairType::CrossWithDot => shapes.push(Shape::Disc {
+            center: at([0.0, 0.0]),
+            radius: config.size,
+        }),
+        CrosshairType::Circle => shapes.push(Shape::Ring {
+            center: at([0.0, 0.0]),
+            radius: config.size,
+            width: config.thickness,
+        }),
+        CrosshairType::Custom => shapes.extend(config.primitives.iter().map(|primitive| match *primitive {
+            CrosshairPrimitive::Dot { center, radius } => Shape::Disc { center: at(center), radius },
+            CrosshairPrimitive::Circle { center, radius } => Shape::Ring {
+                center: at(center),
+                radius,
+                width: config.thickness,
+            },
+            CrosshairPrimitive::Line { from, to } => Shape::Segment {
+                from: at(from),
+                to: at(to),
+                width: config.thickness,
+            },
+        })),
+        CrosshairType::Cross | CrosshairType::T => {}
+    }
+    shapes
+}
+
+/// Reasons a share code cannot be imported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShareCodeError {
+    /// The text does not start like a share code.
+    Malformed,
+    /// The checksum does not match, e.g. because the code was cut off or mistyped.
+    Corrupted,
+    /// The code was made by a newer aim2go.
+    Unsupported(u8),
+    /// The code is intact, but the crosshair it holds is not valid.
+    Invalid(String),
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareCodeError::Malformed => {
+                write!(f, "not a crosshair share code; share codes start with `{}`", SHARE_CODE_PREFIX)
+            }
+            ShareCodeError::Corrupted => write!(f, "the share code is corrupted; check that all of it was copied"),
+            ShareCodeError::Unsupported(version) => write!(
+                f,
+                "the share code has format version {}, but this aim2go reads up to version {}; update aim2go to import it",
+                version, SHARE_CODE_VERSION
+            ),
+            ShareCodeError::Invalid(e) => write!(f, "the share code holds an invalid crosshair: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShareCodeError {}
+
+/// Encodes crosshair settings into a share code, with lengths rounded to a tenth of a pixel.
+///
+/// The code is `A2G-` and URL-safe base64 of: the format version, the settings, and a
+/// CRC-32 of both.
+pub fn encode_share_code(config: &CrosshairConfig) -> String {
+    let mut bytes = vec![SHARE_CODE_VERSION];
+    let type_index = CrosshairType::ALL
+        .iter()
+        .position(|crosshair_type| *crosshair_type == config.crosshair_type)
+        .unwrap_or(0);
+    bytes.push(type_index as u8);
+    for length in [config.size, config.gap, config.arm_length, config.thickness, config.outline_width] {
+        bytes.extend(((length * 10.0).round() as u16).to_le_bytes());
+    }
+    let Rgb(r, g, b) = config.outline_color;
+    bytes.extend([r, g, b, (config.fill_opacity * 100.0).round() as u8]);
+    push_point(&mut bytes, config.offset);
+
+    bytes.push(config.primitives.len().min(u8::MAX as usize) as u8);
+    for primitive in config.primitives.iter().take(u8::MAX as usize) {
+        let (tag, points, radius) = match *primitive {
+            CrosshairPrimitive::Dot { center, radius } => (0, vec![center], Some(radius)),
+            CrosshairPrimitive::Circle { center, radius } => (1, vec![center], Some(radius)),
+            CrosshairPrimitive::Line { from, to } => (2, vec![from, to], None),
+        };
+        bytes.push(tag);
+        for point in points {
+            push_point(&mut bytes, point);
+        }
+        if let Some(radius) = radius {
+            bytes.extend(tenths(radius).to_le_bytes());
+        }
+    }
+
+    let checksum = crc32fast::hash(&bytes);
+    bytes.extend(checksum.to_le_bytes());
+    format!("{}{}", SHARE_CODE_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Decodes a share code made by [`encode_share_code`], checking it before use.
+pub fn decode_share_code(code: &str) -> Result<CrosshairConfig, ShareCodeError> {
+    let encoded = code.trim().strip_prefix(SHARE_CODE_PREFIX).ok_or(ShareCodeError::Malformed)?;
+    // Past the prefix, bad base64 is most likely a code that was cut off or mistyped.
+    let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| ShareCodeError::Corrupted)?;
+    if bytes.len() < 5 {
+        return Err(ShareCodeError::Corrupted);
+    }
+    let (data, checksum) = bytes.split_at(bytes.len() - 4);
+    if crc32fast::hash(data).to_le_bytes() != checksum {
+        return Err(ShareCodeError::Corrupted);
+    }
+    // Only trust the version once the checksum says it was not mangled.
+    let version = data[0];
+    if version > SHARE_CODE_VERSION {
+        return Err(ShareCodeError::Unsupported(version));
+    }
+
+    let config = read_crosshair(&mut Reader(&data[1..])).ok_or(ShareCodeError::Corrupted)?;
+    config.validate().map_err(ShareCodeError::Invalid)?;
+    Ok(config)
+}
+
+/// Reads the settings of a version 1 share code, or `None` if they are cut short.
+fn read_crosshair(reader: &mut Reader) -> Option<CrosshairConfig> {
+    let crosshair_type = CrosshairType::ALL.get(reader.u8()? as usize)?.clone();
+    let mut lengths = [0.0; 5];
+    for length in &mut lengths {
+        *length = reader.u16()? as f32 / 10.0;
+    }
+    let [size, gap, arm_length, thickness, outline_width] = lengths;
+    let outline_color = Rgb(reader.u8()?, reader.u8()?, reader.u8()?);
+    let fill_opacity = reader.u8()? as f32 / 100.0;
+    let offset = reader.point()?;
+
+    let mut primitives = Vec::new();
+    for _ in 0..reader.u8()? {
+        primitives.push(match reader.u8()? {
+            0 => CrosshairPrimitive::Dot { center: reader.point()?, radius: reader.i16()? as f32 / 10.0 },
+            1 => CrosshairPrimitive::Circle { center: reader.point()?, radius: reader.i16()? as f32 / 10.0 },
+            2 => CrosshairPrimitive::Line { from: reader.point()?, to: reader.point()? },
+            _ => return None,
+        });
+    }
+    if !reader.0.is_empty() {
+        return None;
+    }
+
+    Some(CrosshairConfig {
+        crosshair_type,
+        size,
+        gap,
+        arm_length,
+        thickness,
+        outline_color,
+        outline_width,
+        fill_opacity,
+        offset,
+        primitives,
+    })
+}
+
+fn tenths(value: f32) -> i16 {
+    (value * 10.0).round() as i16
+}
+
+fn push_point(bytes: &mut Vec<u8>, point: Point) {
+    bytes.extend(tenths(point[0]).to_le_bytes());
+    bytes.extend(tenths(point[1]).to_le_bytes());
+}
+
+/// Reads little-endian numbers off the front of a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (bytes, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        self.take().map(i16::from_le_bytes)
+    }
+
+    fn point(&mut self) -> Option<Point> {
+        Some([self.i16()? as f32 / 10.0, self.i16()? as f32 / 10.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disc_and_ring_outlines_only_border_them() {
+        let disc = Shape::Disc { center: [1.0, 2.0], radius: 5.0 };
+        assert_eq!(disc.outline(2.0), [Shape::Ring { center: [1.0, 2.0], radius: 6.0, width: 2.0 }]);
+
+        let ring = Shape::Ring { center: [0.0, 0.0], radius: 8.0, width: 4.0 };
+        assert_eq!(
+            ring.outline(2.0),
+            [
+                Shape::Ring { center: [0.0, 0.0], radius: 11.0, width: 2.0 },
+                Shape::Ring { center: [0.0, 0.0], radius: 5.0, width: 2.0 },
+            ]
+        );
+
+        // A hole narrower than the outline is filled by it.
+        let ring = Shape::Ring { center: [0.0, 0.0], radius: 3.0, width: 4.0 };
+        assert_eq!(ring.outline(2.0)[1], Shape::Disc { center: [0.0, 0.0], radius: 1.0 });
+        let ring = Shape::Ring { center: [0.0, 0.0], radius: 2.0, width: 4.0 };
+        assert_eq!(ring.outline(2.0).len(), 1);
+    }
+
+    #[test]
+    fn segment_outlines_are_four_edge_strips() {
+        let segment = Shape::Segment { from: [0.0, 0.0], to: [10.0, 0.0], width: 4.0 };
+        assert_eq!(
+            segment.outline(1.0),
+            [
+                Shape::Segment { from: [-1.0, 2.5], to: [11.0, 2.5], width: 1.0 },
+                Shape::Segment { from: [-1.0, -2.5], to: [11.0, -2.5], width: 1.0 },
+                Shape::Segment { from: [-0.5, 2.0], to: [-0.5, -2.0], width: 1.0 },
+                Shape::Segment { from: [10.5, -2.0], to: [10.5, 2.0], width: 1.0 },
+            ]
+        );
+        let point = Shape::Segment { from: [1.0, 1.0], to: [1.0, 1.0], width: 4.0 };
+        assert!(point.outline(1.0).is_empty());
+    }
+
+    #[test]
+    fn outlines_are_drawn_before_fills() {
+        let config = CrosshairConfig {
+            crosshair_type: CrosshairType::CrossWithDot,
+            ..Default::default()
+        };
+        let paints: Vec<Paint> = shapes(&config).into_iter().map(|(paint, _)| paint).collect();
+        // Four arms of four strips and a dot of one ring, then the five fills.
+        assert_eq!(paints.len(), 4 * 4 + 1 + 5);
+        assert!(paints[..17].iter().all(|paint| *paint == Paint::Outline));
+        assert!(paints[17..].iter().all(|paint| *paint == Paint::Fill));
+
+        let config = CrosshairConfig { outline_width: 0.0, ..config };
+        assert!(shapes(&config).iter().all(|(paint, _)| *paint == Paint::Fill));
+    }
+
+    /// A crosshair of `crosshair_type` using every setting, with lengths in tenths of a pixel.
+    fn crosshair(crosshair_type: CrosshairType) -> CrosshairConfig {
+        CrosshairConfig {
+            crosshair_type,
+            size: 4.5,
+            gap: 2.5,
+            arm_length: 7.3,
+            thickness: 1.5,
+            outline_color: Rgb(10, 20, 30),
+            outline_width: 0.5,
+            fill_opacity: 0.75,
+            offset: [-3.2, 4.1],
+            primitives: vec![
+                CrosshairPrimitive::Dot { center: [0.0, 0.0], radius: 1.5 },
+                CrosshairPrimitive::Circle { center: [-1.5, 2.0], radius: 10.0 },
+                CrosshairPrimitive::Line { from: [-12.0, 14.0], to: [12.5, -14.0] },
+            ],
+        }
+    }
+
+    /// Wraps `data` in a share code with a valid checksum.
+    fn share_code(mut data: Vec<u8>) -> String {
+        let checksum = crc32fast::hash(&data);
+        data.extend(checksum.to_le_bytes());
+        format!("{}{}", SHARE_CODE_PREFIX, URL_SAFE_NO_PAD.encode(data))
+    }
+
+    /// The data of a share code, without its prefix and checksum.
+    fn share_code_data(code: &str) -> Vec<u8> {
+        let mut bytes = URL_SAFE_NO_PAD.decode(&code[SHARE_CODE_PREFIX.len()..]).unwrap();
+        bytes.truncate(bytes.len() - 4);
+        bytes
+    }
+
+    #[test]
+    fn every_crosshair_type_round_trips() {
+        for crosshair_type in CrosshairType::ALL {
+            let config = crosshair(crosshair_type);
+            config.validate().expect("test crosshairs are valid");
+            let code = encode_share_code(&config);
+            assert!(code.starts_with(SHARE_CODE_PREFIX));
+            assert_eq!(decode_share_code(&code), Ok(config));
+        }
+        // Surrounding whitespace from copying the code is ignored.
+        let config = crosshair(CrosshairType::Dot);
+        assert_eq!(decode_share_code(&format!(" {}\n", encode_share_code(&config))), Ok(config));
+    }
+
+    #[test]
+    fn mistyped_codes_are_corrupted() {
+        let code = encode_share_code(&crosshair(CrosshairType::Custom));
+        for at in SHARE_CODE_PREFIX.len()..code.len() {
+            let mut flipped = code.clone().into_bytes();
+            flipped[at] = if flipped[at] == b'A' { b'B' } else { b'A' };
+            let flipped = String::from_utf8(flipped).unwrap();
+            assert_eq!(decode_share_code(&flipped), Err(ShareCodeError::Corrupted), "{}", flipped);
+        }
+    }
+
+    #[test]
+    fn truncated_codes_are_corrupted() {
+        let code = encode_share_code(&crosshair(CrosshairType::Custom));
+        for len in SHARE_CODE_PREFIX.len()..code.len() {
+            assert_eq!(decode_share_code(&code[..len]), Err(ShareCodeError::Corrupted), "{}", &code[..len]);
+        }
+    }
+
+    #[test]
+    fn codes_without_the_prefix_are_malformed() {
+        let code = encode_share_code(&crosshair(CrosshairType::Cross));
+        assert_eq!(decode_share_code(&code[SHARE_CODE_PREFIX.len()..]), Err(ShareCodeError::Malformed));
+        assert_eq!(decode_share_code(""), Err(ShareCodeError::Malformed));
+        assert_eq!(decode_share_code("a2g-AAAA"), Err(ShareCodeError::Malformed));
+    }
+
+    #[test]
+    fn codes_from_a_newer_version_are_unsupported() {
+        let mut data = share_code_data(&encode_share_code(&crosshair(CrosshairType::Cross)));
+        data[0] = SHARE_CODE_VERSION + 1;
+        assert_eq!(
+            decode_share_code(&share_code(data)),
+            Err(ShareCodeError::Unsupported(SHARE_CODE_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn intact_codes_of_invalid_crosshairs_are_invalid() {
+        let config = CrosshairConfig {
+            size: CrosshairConfig::MAX_LENGTH + 1.0,
+            ..crosshair(CrosshairType::Dot)
+        };
+        assert!(matches!(decode_share_code(&encode_share_code(&config)), Err(ShareCodeError::Invalid(_))));
+
+        let config = CrosshairConfig {
+            primitives: Vec::new(),
+            ..crosshair(CrosshairType::Custom)
+        };
+        assert!(matches!(decode_share_code(&encode_share_code(&config)), Err(ShareCodeError::Invalid(_))));
+    }
+
+    #[test]
+    fn intact_codes_with_bad_data_are_corrupted() {
+        let data = share_code_data(&encode_share_code(&crosshair(CrosshairType::Custom)));
+
+        let mut unknown_type = data.clone();
+        unknown_type[1] = CrosshairType::ALL.len() as u8;
+        assert_eq!(decode_share_code(&share_code(unknown_type)), Err(ShareCodeError::Corrupted));
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(decode_share_code(&share_code(trailing)), Err(ShareCodeError::Corrupted));
+
+        let mut short = data;
+        short.pop();
+        assert_eq!(decode_share_code(&share_code(short)), Err(ShareCodeError::Corrupted));
+    }
+}
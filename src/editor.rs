@@ -0,0 +1,663 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use aim2go::config::{
+    update_game_config, Actions, ChordError, CrosshairConfig, CrosshairType, GameConfig, KeyChord, LayerConfig,
+    MouseChord, OverlayLayer, TuiCommand,
+};
+use aim2go::theme::Rgb;
+
+/// Keybind map a binding belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Keyboard,
+    Mouse,
+}
+
+/// A line of the config form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Row {
+    /// Section title; never selectable.
+    Header(&'static str),
+    /// Toggle for `OverlayLayer::ALL[index]`.
+    Layer(usize),
+    CrosshairType,
+    /// A number of the crosshair settings
+    Crosshair(CrosshairField),
+    OutlineColor,
+    /// Binding at `index` of a keybind map.
+    Binding(Section, usize),
+}
+
+/// A number of the crosshair settings, edited by typing or stepped with left and right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrosshairField {
+    Size,
+    Gap,
+    ArmLength,
+    Thickness,
+    OutlineWidth,
+    FillOpacity,
+    OffsetX,
+    OffsetY,
+}
+
+impl CrosshairField {
+    const ALL: [CrosshairField; 8] = [
+        CrosshairField::Size,
+        CrosshairField::Gap,
+        CrosshairField::ArmLength,
+        CrosshairField::Thickness,
+        CrosshairField::OutlineWidth,
+        CrosshairField::FillOpacity,
+        CrosshairField::OffsetX,
+        CrosshairField::OffsetY,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            CrosshairField::Size => "Size",
+            CrosshairField::Gap => "Gap",
+            CrosshairField::ArmLength => "Arm length",
+            CrosshairField::Thickness => "Thickness",
+            CrosshairField::OutlineWidth => "Outline width",
+            CrosshairField::FillOpacity => "Fill opacity",
+            CrosshairField::OffsetX => "Offset X",
+            CrosshairField::OffsetY => "Offset Y",
+        }
+    }
+
+    /// How much left and right change the value.
+    fn step(self) -> f32 {
+        match self {
+            CrosshairField::FillOpacity => 0.1,
+            _ => 1.0,
+        }
+    }
+
+    fn value_mut(self, crosshair: &mut CrosshairConfig) -> &mut f32 {
+        match self {
+            CrosshairField::Size => &mut crosshair.size,
+            CrosshairField::Gap => &mut crosshair.gap,
+            CrosshairField::ArmLength => &mut crosshair.arm_length,
+            CrosshairField::Thickness => &mut crosshair.thickness,
+            CrosshairField::OutlineWidth => &mut crosshair.outline_width,
+            CrosshairField::FillOpacity => &mut crosshair.fill_opacity,
+            CrosshairField::OffsetX => &mut crosshair.offset[0],
+            CrosshairField::OffsetY => &mut crosshair.offset[1],
+        }
+    }
+
+    fn value(self, crosshair: &CrosshairConfig) -> f32 {
+        match self {
+            CrosshairField::Size => crosshair.size,
+            CrosshairField::Gap => crosshair.gap,
+            CrosshairField::ArmLength => crosshair.arm_length,
+            CrosshairField::Thickness => crosshair.thickness,
+            CrosshairField::OutlineWidth => crosshair.outline_width,
+            CrosshairField::FillOpacity => crosshair.fill_opacity,
+            CrosshairField::OffsetX => crosshair.offset[0],
+            CrosshairField::OffsetY => crosshair.offset[1],
+        }
+    }
+}
+
+/// Message shown below the form after an action.
+#[derive(Debug, Clone)]
+pub enum Status {
+    Info(String),
+    Error(String),
+}
+
+/// Form-based editor for a game's overlay and keybind settings.
+#[derive(Debug)]
+pub struct ConfigEditor {
+    /// Game the config belongs to
+    game: String,
+    /// Last saved settings
+    saved: GameConfig,
+    /// Enabled overlay layers
+    pub layers: Vec<LayerConfig>,
+    /// Crosshair shape and style
+    pub crosshair: CrosshairConfig,
+    /// Keyboard bindings, sorted by chord
+    pub keyboard: Vec<(String, Actions)>,
+    /// Mouse bindings, sorted by chord
+    pub mouse: Vec<(String, Actions)>,
+    /// Index of the selected row
+    pub selected: usize,
+    /// Text typed into the selected field while it is being edited
+    pub input: Option<String>,
+    /// Are there unsaved changes?
+    pub dirty: bool,
+    /// Result of the last action
+    pub status: Option<Status>,
+}
+
+impl ConfigEditor {
+    /// Constructs a new instance of [`ConfigEditor`].
+    pub fn new(game: &str, config: &GameConfig) -> Self {
+        let mut editor = Self {
+            game: game.to_string(),
+            saved: config.clone(),
+            layers: Vec::new(),
+            crosshair: CrosshairConfig::default(),
+            keyboard: Vec::new(),
+            mouse: Vec::new(),
+            selected: 1,
+            input: None,
+            dirty: false,
+            status: None,
+        };
+        editor.load(config);
+        editor
+    }
+
+    /// Replaces the form contents with `config`, discarding unsaved changes.
+    pub fn load(&mut self, config: &GameConfig) {
+        self.saved = config.clone();
+        self.layers = config.overlay.layers.clone();
+        self.crosshair = config.overlay.crosshair.clone();
+        self.keyboard = sorted_bindings(&config.keybinds.keyboard);
+        self.mouse = sorted_bindings(&config.keybinds.mouse);
+        self.input = None;
+        self.dirty = false;
+        self.clamp_selection();
+    }
+
+    /// Returns every line of the form, in display order.
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![Row::Header("Overlay layers")];
+        rows.extend((0..OverlayLayer::ALL.len()).map(Row::Layer));
+        rows.push(Row::Header("Crosshair"));
+        rows.push(Row::CrosshairType);
+        rows.extend(CrosshairField::ALL.map(Row::Crosshair));
+        rows.push(Row::OutlineColor);
+        rows.push(Row::Header("Keyboard bindings"));
+        rows.extend((0..self.keyboard.len()).map(|index| Row::Binding(Section::Keyboard, index)));
+        rows.push(Row::Header("Mouse bindings"));
+        rows.extend((0..self.mouse.len()).map(|index| Row::Binding(Section::Mouse, index)));
+        rows
+    }
+
+    /// Returns the label and value shown for a row.
+    pub fn row_text(&self, row: Row) -> (String, String) {
+        match row {
+            Row::Header(title) => (title.to_string(), String::new()),
+            Row::Layer(index) => {
+                let layer = OverlayLayer::ALL[index];
+                let enabled = self.layers.iter().any(|config| config.layer == layer);
+                (layer.to_string(), if enabled { "[x]" } else { "[ ]" }.to_string())
+            }
+            Row::CrosshairType => ("Type".to_string(), format!("< {:?} >", self.crosshair.crosshair_type)),
+            Row::Crosshair(field) => (field.label().to_string(), format!("< {} >", field.value(&self.crosshair))),
+            Row::OutlineColor => ("Outline colour".to_string(), self.crosshair.outline_color.to_string()),
+            Row::Binding(section, index) => {
+                let (chord, action) = &self.bindings(section)[index];
+                (chord.clone(), format!("< {:?} >", action))
+            }
+        }
+    }
+
+    /// Handles a key press and the command it is bound to, returning `false` if the
+    /// editor did not use it.
+    ///
+    /// While a field is being edited, keys are typed into it rather than run as commands.
+    pub fn handle_key(&mut self, key_event: KeyEvent, command: Option<TuiCommand>) -> bool {
+        if let Some(input) = &mut self.input {
+            match key_event.code {
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => input.push(c),
+                _ if command == Some(TuiCommand::Quit) => return false,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => self.commit_input(),
+                KeyCode::Esc => self.cancel_input(),
+                _ => {}
+            }
+            return true;
+        }
+
+        match command {
+            Some(TuiCommand::MoveDown) => self.move_selection(1),
+            Some(TuiCommand::MoveUp) => self.move_selection(-1),
+            Some(TuiCommand::MoveLeft) => self.cycle(-1),
+            Some(TuiCommand::MoveRight) => self.cycle(1),
+            Some(TuiCommand::Select) => self.activate(),
+            Some(TuiCommand::AddItem) => self.add_binding(),
+            Some(TuiCommand::RemoveItem) => self.remove_binding(),
+            Some(TuiCommand::Save) => self.save(),
+            Some(TuiCommand::Revert) => {
+                let saved = self.saved.clone();
+                self.load(&saved);
+                self.status = Some(Status::Info("Reverted to the saved config.".to_string()));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns the currently selected row.
+    pub fn selected_row(&self) -> Row {
+        self.rows()[self.selected]
+    }
+
+    fn bindings(&self, section: Section) -> &Vec<(String, Actions)> {
+        match section {
+            Section::Keyboard => &self.keyboard,
+            Section::Mouse => &self.mouse,
+        }
+    }
+
+    fn bindings_mut(&mut self, section: Section) -> &mut Vec<(String, Actions)> {
+        match section {
+            Section::Keyboard => &mut self.keyboard,
+            Section::Mouse => &mut self.mouse,
+        }
+    }
+
+    /// Moves the selection to the next field in `direction`, skipping headers.
+    fn move_selection(&mut self, direction: isize) {
+        let rows = self.rows();
+        let mut index = self.selected;
+        loop {
+            index = (index as isize + direction).rem_euclid(rows.len() as isize) as usize;
+            if !matches!(rows[index], Row::Header(_)) {
+                break;
+            }
+        }
+        self.selected = index;
+    }
+
+    /// Keeps the selection on a field after rows were added or removed.
+    fn clamp_selection(&mut self) {
+        let rows = self.rows();
+        self.selected = self.selected.min(rows.len() - 1);
+        if matches!(rows[self.selected], Row::Header(_)) {
+            self.move_selection(-1);
+        }
+    }
+
+    /// Toggles, cycles or starts editing the selected field.
+    fn activate(&mut self) {
+        match self.selected_row() {
+            Row::Header(_) => {}
+            Row::Layer(index) => {
+                let layer = OverlayLayer::ALL[index];
+                if let Some(position) = self.layers.iter().position(|config| config.layer == layer) {
+                    self.layers.remove(position);
+                } else {
+                    // Bring back the saved settings of a layer switched off by mistake.
+                    let saved = self.saved.overlay.layers.iter().find(|config| config.layer == layer);
+                    self.layers.push(saved.cloned().unwrap_or_else(|| layer.default_config()));
+                }
+                self.mark_dirty();
+            }
+            Row::CrosshairType => self.cycle(1),
+            Row::Crosshair(field) => self.input = Some(field.value(&self.crosshair).to_string()),
+            Row::OutlineColor => self.input = Some(self.crosshair.outline_color.to_string()),
+            Row::Binding(section, index) => self.input = Some(self.bindings(section)[index].0.clone()),
+        }
+    }
+
+    /// Steps the value of the selected field forwards or backwards.
+    fn cycle(&mut self, direction: isize) {
+        match self.selected_row() {
+            Row::CrosshairType => {
+                // Skip types the other settings don't work with, e.g. `Custom` without primitives.
+                let mut crosshair = self.crosshair.clone();
+                for _ in 0..CrosshairType::ALL.len() {
+                    crosshair.crosshair_type = step(&CrosshairType::ALL, &crosshair.crosshair_type, direction);
+                    if crosshair.validate().is_ok() {
+                        break;
+                    }
+                }
+                if crosshair.validate().is_ok() {
+                    self.crosshair = crosshair;
+                    self.mark_dirty();
+                }
+            }
+            Row::Crosshair(field) => {
+                let value = field.value(&self.crosshair) + direction as f32 * field.step();
+                // Round away the error of adding up tenths.
+                match self.set_crosshair_value(field, (value * 100.0).round() / 100.0) {
+                    Ok(()) => self.mark_dirty(),
+                    Err(e) => self.status = Some(Status::Error(e)),
+                }
+            }
+            Row::Binding(section, index) => {
+                let action = step(&Actions::ALL, &self.bindings(section)[index].1, direction);
+                self.bindings_mut(section)[index].1 = action;
+                self.mark_dirty();
+            }
+            Row::Layer(_) => self.activate(),
+            Row::Header(_) | Row::OutlineColor => {}
+        }
+    }
+
+    /// Adds an empty binding to the selected map and starts editing its chord.
+    fn add_binding(&mut self) {
+        let section = match self.selected_row() {
+            Row::Binding(section, _) => section,
+            _ => Section::Keyboard,
+        };
+        self.bindings_mut(section).push((String::new(), Actions::ALL[0].clone()));
+        let index = self.bindings(section).len() - 1;
+        self.selected = self
+            .rows()
+            .iter()
+            .position(|row| *row == Row::Binding(section, index))
+            .unwrap_or(self.selected);
+        self.input = Some(String::new());
+    }
+
+    /// Removes the selected binding.
+    fn remove_binding(&mut self) {
+        if let Row::Binding(section, index) = self.selected_row() {
+            self.bindings_mut(section).remove(index);
+            self.clamp_selection();
+            self.mark_dirty();
+        }
+    }
+
+    /// Validates and applies the text typed into the selected field.
+    fn commit_input(&mut self) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+
+        let result = match self.selected_row() {
+            Row::Crosshair(field) => match input.trim().parse::<f32>() {
+                Ok(value) => self.set_crosshair_value(field, value),
+                Err(_) => Err(format!("'{}' is not a number.", input.trim())),
+            },
+            Row::OutlineColor => input.parse::<Rgb>().map(|color| self.crosshair.outline_color = color),
+            Row::Binding(section, index) => canonical_chord(section, &input).and_then(|chord| {
+                let duplicate = self
+                    .bindings(section)
+                    .iter()
+                    .enumerate()
+                    .any(|(other, (existing, _))| {
+                        other != index && canonical_chord(section, existing).is_ok_and(|existing| existing == chord)
+                    });
+                if duplicate {
+                    Err(format!("'{}' is already bound.", chord))
+                } else {
+                    self.bindings_mut(section)[index].0 = chord;
+                    Ok(())
+                }
+            }),
+            _ => Ok(()),
+        };
+
+        match result {
+            Ok(()) => self.mark_dirty(),
+            Err(e) => {
+                // Keep editing so the value can be fixed.
+                self.input = Some(input);
+                self.status = Some(Status::Error(e));
+            }
+        }
+    }
+
+    /// Stops editing without applying; a binding that never got a chord is dropped.
+    fn cancel_input(&mut self) {
+        self.input = None;
+        if let Row::Binding(section, index) = self.selected_row() {
+            if self.bindings(section)[index].0.is_empty() {
+                self.bindings_mut(section).remove(index);
+                self.clamp_selection();
+            }
+        }
+        self.status = None;
+    }
+
+    /// Sets a crosshair number if the crosshair stays valid with it.
+    fn set_crosshair_value(&mut self, field: CrosshairField, value: f32) -> Result<(), String> {
+        let mut crosshair = self.crosshair.clone();
+        *field.value_mut(&mut crosshair) = value;
+        crosshair.validate().map_err(|e| format!("{}: {}.", field.label(), e))?;
+        self.crosshair = crosshair;
+        Ok(())
+    }
+
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.status = None;
+    }
+
+    /// Builds the game config described by the form.
+    pub fn to_game_config(&self) -> GameConfig {
+        let mut config = self.saved.clone();
+        config.overlay.layers = self.layers.clone();
+        config.overlay.crosshair = self.crosshair.clone();
+        config.keybinds.keyboard = self.keyboard.iter().cloned().collect();
+        config.keybinds.mouse = self.mouse.iter().cloned().collect();
+        config
+    }
+
+    /// Writes the form to the game's `config.yaml`.
+    fn save(&mut self) {
+        if self.keyboard.iter().chain(&self.mouse).any(|(chord, _)| chord.is_empty()) {
+            self.status = Some(Status::Error("Every binding needs a key.".to_string()));
+            return;
+        }
+
+        let config = self.to_game_config();
+        match update_game_config(&self.game, config.clone()) {
+            Ok(()) => {
+                self.saved = config;
+                self.dirty = false;
+                self.status = Some(Status::Info("Saved to config.yaml.".to_string()));
+            }
+            Err(e) => self.status = Some(Status::Error(e.summary())),
+        }
+    }
+}
+
+/// Returns the bindings of a keybind map sorted by chord, so rows stay in place.
+fn sorted_bindings(map: &HashMap<String, Actions>) -> Vec<(String, Actions)> {
+    let mut bindings: Vec<(String, Actions)> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    bindings.sort_by(|a, b| a.0.cmp(&b.0));
+    bindings
+}
+
+/// Returns the value `direction` steps away from `current` in `values`, wrapping around.
+fn step<T: Clone + PartialEq>(values: &[T], current: &T, direction: isize) -> T {
+    let index = values.iter().position(|value| value == current).unwrap_or(0);
+    let next = (index as isize + direction).rem_euclid(values.len() as isize) as usize;
+    values[next].clone()
+}
+
+/// Parses a chord for the given map and returns its canonical spelling.
+fn canonical_chord(section: Section, input: &str) -> Result<String, String> {
+    let chord = match section {
+        Section::Keyboard => input.parse::<KeyChord>().map(|chord| chord.to_string()),
+        Section::Mouse => input.parse::<MouseChord>().map(|chord| chord.to_string()),
+    };
+    chord.map_err(|e: ChordError| match e.suggestion() {
+        Some(suggestion) => format!("Invalid chord: {} (did you mean `{}`?)", e, suggestion),
+        None => format!("Invalid chord: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aim2go::config::Config;
+
+    fn editor() -> ConfigEditor {
+        ConfigEditor::new("test", &Config::default().game)
+    }
+
+    fn select(editor: &mut ConfigEditor, row: Row) {
+        editor.selected = editor.rows().iter().position(|candidate| *candidate == row).expect("the row exists");
+    }
+
+    fn run(editor: &mut ConfigEditor, command: TuiCommand) -> bool {
+        editor.handle_key(KeyEvent::new(KeyCode::Null, KeyModifiers::NONE), Some(command))
+    }
+
+    /// Replaces the text of the field being edited with `text`, then presses `key`.
+    fn type_text(editor: &mut ConfigEditor, text: &str, key: KeyCode) {
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        while editor.input.as_ref().is_some_and(|input| !input.is_empty()) {
+            editor.handle_key(press(KeyCode::Backspace), None);
+        }
+        for c in text.chars() {
+            editor.handle_key(press(KeyCode::Char(c)), None);
+        }
+        editor.handle_key(press(key), None);
+    }
+
+    fn error(editor: &ConfigEditor) -> &str {
+        match &editor.status {
+            Some(Status::Error(e)) => e,
+            status => panic!("expected an error, got {:?}", status),
+        }
+    }
+
+    #[test]
+    fn typed_values_are_applied() {
+        let mut editor = editor();
+        select(&mut editor, Row::Crosshair(CrosshairField::Size));
+
+        assert!(run(&mut editor, TuiCommand::Select));
+        assert_eq!(editor.input.as_deref(), Some("5"));
+        type_text(&mut editor, "12", KeyCode::Enter);
+
+        assert_eq!(editor.crosshair.size, 12.0);
+        assert_eq!(editor.input, None);
+        assert!(editor.dirty);
+        assert_eq!(editor.to_game_config().overlay.crosshair.size, 12.0);
+    }
+
+    #[test]
+    fn values_are_stepped_with_left_and_right() {
+        let mut editor = editor();
+        select(&mut editor, Row::Crosshair(CrosshairField::Size));
+        run(&mut editor, TuiCommand::MoveRight);
+        assert_eq!(editor.crosshair.size, 6.0);
+
+        select(&mut editor, Row::Crosshair(CrosshairField::FillOpacity));
+        run(&mut editor, TuiCommand::MoveLeft);
+        run(&mut editor, TuiCommand::MoveLeft);
+        assert_eq!(editor.crosshair.fill_opacity, 0.8);
+        assert!(editor.dirty);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_and_kept_for_fixing() {
+        let mut editor = editor();
+        select(&mut editor, Row::Crosshair(CrosshairField::FillOpacity));
+
+        // Out of range, as `CrosshairConfig::validate` says.
+        run(&mut editor, TuiCommand::MoveRight);
+        assert_eq!(error(&editor), "Fill opacity: `fill_opacity` must be between 0 and 1, found 1.1.");
+        assert_eq!(editor.crosshair.fill_opacity, 1.0);
+
+        run(&mut editor, TuiCommand::Select);
+        type_text(&mut editor, "2", KeyCode::Enter);
+        assert_eq!(error(&editor), "Fill opacity: `fill_opacity` must be between 0 and 1, found 2.");
+        assert_eq!(editor.input.as_deref(), Some("2"));
+
+        type_text(&mut editor, "lots", KeyCode::Enter);
+        assert_eq!(error(&editor), "'lots' is not a number.");
+
+        type_text(&mut editor, "", KeyCode::Esc);
+        assert_eq!(editor.input, None);
+        assert!(editor.status.is_none());
+        assert_eq!(editor.crosshair.fill_opacity, 1.0);
+        assert!(!editor.dirty);
+    }
+
+    #[test]
+    fn a_crosshair_type_the_settings_dont_allow_is_skipped() {
+        let mut editor = editor();
+        select(&mut editor, Row::CrosshairType);
+
+        // `Custom` needs primitives, which the defaults don't have.
+        for _ in 0..CrosshairType::ALL.len() - 1 {
+            run(&mut editor, TuiCommand::MoveRight);
+            assert_ne!(editor.crosshair.crosshair_type, CrosshairType::Custom);
+            assert!(editor.crosshair.validate().is_ok());
+        }
+        assert_eq!(editor.crosshair.crosshair_type, CrosshairType::Dot);
+    }
+
+    #[test]
+    fn revert_discards_unsaved_changes() {
+        let mut editor = editor();
+        select(&mut editor, Row::Crosshair(CrosshairField::Gap));
+        run(&mut editor, TuiCommand::MoveRight);
+        select(&mut editor, Row::Layer(0));
+        run(&mut editor, TuiCommand::Select);
+        assert!(editor.dirty);
+
+        run(&mut editor, TuiCommand::Revert);
+        assert!(!editor.dirty);
+        assert_eq!(editor.crosshair, Config::default().game.overlay.crosshair);
+        assert_eq!(editor.layers, Config::default().game.overlay.layers);
+        assert!(matches!(&editor.status, Some(Status::Info(_))));
+    }
+
+    #[test]
+    fn layers_are_toggled_back_with_their_saved_settings() {
+        let mut editor = editor();
+        let saved = editor.layers.clone();
+        let layer = saved[0].layer;
+        let index = OverlayLayer::ALL.iter().position(|candidate| *candidate == layer).unwrap();
+        select(&mut editor, Row::Layer(index));
+
+        run(&mut editor, TuiCommand::Select);
+        assert!(editor.layers.iter().all(|config| config.layer != layer));
+        assert_eq!(editor.row_text(Row::Layer(index)).1, "[ ]");
+
+        run(&mut editor, TuiCommand::Select);
+        assert_eq!(editor.layers.iter().find(|config| config.layer == layer), saved.first());
+    }
+
+    #[test]
+    fn bindings_are_added_and_removed() {
+        let mut editor = editor();
+        let bindings = editor.keyboard.len();
+
+        run(&mut editor, TuiCommand::AddItem);
+        assert_eq!(editor.selected_row(), Row::Binding(Section::Keyboard, bindings));
+        type_text(&mut editor, "ctrl + k", KeyCode::Enter);
+        assert_eq!(editor.keyboard[bindings], ("CTRL+K".to_string(), Actions::ALL[0].clone()));
+        assert!(editor.dirty);
+        assert!(editor.to_game_config().keybinds.keyboard.contains_key("CTRL+K"));
+
+        // A chord can only be bound once; giving up on the new binding drops it.
+        run(&mut editor, TuiCommand::AddItem);
+        type_text(&mut editor, "CTRL+K", KeyCode::Enter);
+        assert_eq!(error(&editor), "'CTRL+K' is already bound.");
+        type_text(&mut editor, "", KeyCode::Esc);
+        assert_eq!(editor.keyboard.len(), bindings + 1);
+
+        select(&mut editor, Row::Binding(Section::Keyboard, bindings));
+        run(&mut editor, TuiCommand::RemoveItem);
+        assert_eq!(editor.keyboard.len(), bindings);
+        assert!(!editor.to_game_config().keybinds.keyboard.contains_key("CTRL+K"));
+        assert!(matches!(editor.selected_row(), Row::Binding(..)));
+    }
+
+    #[test]
+    fn invalid_chords_are_rejected() {
+        let mut editor = editor();
+        run(&mut editor, TuiCommand::AddItem);
+        type_text(&mut editor, "CTRL+", KeyCode::Enter);
+
+        assert!(error(&editor).starts_with("Invalid chord"), "{}", error(&editor));
+        assert_eq!(editor.input.as_deref(), Some("CTRL+"));
+    }
+
+    #[test]
+    fn selection_skips_headers() {
+        let mut editor = editor();
+        for _ in 0..editor.rows().len() * 2 {
+            run(&mut editor, TuiCommand::MoveDown);
+            assert!(!matches!(editor.selected_row(), Row::Header(_)));
+        }
+    }
+}
@@ -2,12 +2,18 @@ use std::time::Duration;
 
 use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
 use futures::{FutureExt, StreamExt};
-use tokio::sync::mpsc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+
+use aim2go::config::watch::ConfigReload;
+use aim2go::window::WindowHandle;
 
 use crate::app::AppResult;
+use crate::log::LogEntry;
+use crate::thread::WorkerStatus;
 
-/// Terminal events.
-#[derive(Clone, Copy, Debug)]
+/// Terminal and app events.
+#[derive(Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -17,6 +23,50 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Published by a background task.
+    App(AppEvent),
+}
+
+/// Events that background tasks publish to the TUI.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// A worker changed state.
+    WorkerStatus(WorkerStatus),
+    /// An entry for the execution log.
+    Log(LogEntry),
+    /// A watched config file was edited.
+    ConfigChanged(ConfigReload),
+    /// The game window was closed.
+    WindowLost(WindowHandle),
+    /// Rendering statistics of the overlay.
+    OverlayStats(OverlayStats),
+}
+
+/// Rendering statistics the overlay publishes about once a second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct OverlayStats {
+    /// Frames drawn per second
+    pub fps: f32,
+}
+
+/// Publishes [`AppEvent`]s to the TUI event loop; cheap to clone and usable from any thread.
+#[derive(Debug, Clone)]
+pub struct AppEventSender(mpsc::UnboundedSender<Event>);
+
+impl AppEventSender {
+    /// Sends an event; it is dropped if the TUI has already exited.
+    pub fn send(&self, event: AppEvent) {
+        let _ = self.0.send(Event::App(event));
+    }
+}
+
+#[cfg(test)]
+impl AppEventSender {
+    /// Creates a sender together with its receiving end, in place of an [`EventHandler`].
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (AppEventSender(sender), receiver)
+    }
 }
 
 /// Terminal event handler.
@@ -29,17 +79,19 @@ pub struct EventHandler {
     receiver: mpsc::UnboundedReceiver<Event>,
     /// Event handler thread.
     handler: tokio::task::JoinHandle<()>,
+    /// Tick rate in milliseconds, shared with the handler thread.
+    tick_rate: watch::Sender<u64>,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
     pub fn new(tick_rate: u64) -> Self {
-        let tick_rate = Duration::from_millis(tick_rate);
+        let (tick_rate, mut tick_rate_receiver) = watch::channel(tick_rate);
         let (sender, receiver) = mpsc::unbounded_channel();
         let _sender = sender.clone();
         let handler = tokio::spawn(async move {
             let mut reader = crossterm::event::EventStream::new();
-            let mut tick = tokio::time::interval(tick_rate);
+            let mut tick = tokio::time::interval(Duration::from_millis(*tick_rate_receiver.borrow()));
             loop {
                 let tick_delay = tick.tick();
                 let crossterm_event = reader.next().fuse();
@@ -47,6 +99,9 @@ impl EventHandler {
                   _ = _sender.closed() => {
                     break;
                   }
+                  Ok(()) = tick_rate_receiver.changed() => {
+                    tick = tokio::time::interval(Duration::from_millis(*tick_rate_receiver.borrow()));
+                  }
                   _ = tick_delay => {
                     _sender.send(Event::Tick).unwrap();
                   }
@@ -78,9 +133,24 @@ impl EventHandler {
             sender,
             receiver,
             handler,
+            tick_rate,
         }
     }
 
+    /// Returns a sender that background tasks publish [`AppEvent`]s through.
+    pub fn app_sender(&self) -> AppEventSender {
+        AppEventSender(self.sender.clone())
+    }
+
+    /// Changes the tick rate (in milliseconds) of the running handler thread.
+    pub fn set_tick_rate(&self, tick_rate: u64) {
+        self.tick_rate.send_if_modified(|current| {
+            let changed = *current != tick_rate;
+            *current = tick_rate;
+            changed
+        });
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -89,9 +159,6 @@ impl EventHandler {
         self.receiver
             .recv()
             .await
-            .ok_or(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "This is an IO error",
-            )))
+            .ok_or(Box::new(std::io::Error::other("This is an IO error")))
     }
 }
@@ -1,48 +1,88 @@
 use crate::app::{App, AppResult, Mode};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::keymap::types_text;
+use crate::log::LogLevel;
+use crate::prompt::PromptEvent;
+use crossterm::event::KeyEvent;
+use aim2go::config::TuiCommand;
 
 /// Handles the key events and updates the state of [`App`].
 pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // Exit application on `ESC` or `q`
-        KeyCode::Char('q') => {
-            app.is_overlay_active = false;
-            app.check_overlay_status().await;
-            app.quit();
+    let command = app.keymap.command(&key_event);
+    // Unsaved edits are only discarded if quitting is asked for twice in a row.
+    if command != Some(TuiCommand::Quit) {
+        app.quit_warned = false;
+    }
+
+    // The config form gets first pick so typing into a field doesn't run commands.
+    if let Mode::Config = app.mode {
+        let handled = app.config_editor.handle_key(key_event, command);
+        app.preview_config_edits();
+        if handled {
+            return Ok(());
         }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
+    }
 
+    // Likewise for the command prompt, except that quitting with a chord always works.
+    let quits = command == Some(TuiCommand::Quit) && !types_text(&key_event);
+    if matches!(app.mode, Mode::Execute) && app.prompt.focused && !quits {
+        match app.prompt.handle_key(key_event) {
+            PromptEvent::Submit(line) => app.run_command(&line).await,
+            PromptEvent::Candidates(candidates) => app.log_message(LogLevel::Info, "PROMPT", candidates.join("  ")),
+            PromptEvent::None => {}
         }
-        KeyCode::Char('j') | KeyCode::Char('J') | KeyCode::Down => {
-            match &app.mode {
-                Mode::Menu => app.update_menu_state(1, app.menu_items.len()),
-                _ => {}
+        return Ok(());
+    }
+
+    let Some(command) = command else {
+        return Ok(());
+    };
+
+    match command {
+        TuiCommand::MoveDown => match app.mode {
+            Mode::Menu => app.update_menu_state(1, app.menu_items.len()),
+            Mode::Execute => app.log_view.scroll(1),
+            _ => {}
+        },
+        TuiCommand::MoveUp => match app.mode {
+            Mode::Menu => app.update_menu_state(-1, app.menu_items.len()),
+            Mode::Execute => app.log_view.scroll(-1),
+            _ => {}
+        },
+        TuiCommand::PageDown => {
+            if let Mode::Execute = app.mode {
+                app.log_view.scroll(app.log_view.page as isize);
             }
         }
-        KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Up => {
-            match &app.mode {
-                Mode::Menu => app.update_menu_state(-1, app.menu_items.len()),
-                _ => {}
+        TuiCommand::PageUp => {
+            if let Mode::Execute = app.mode {
+                app.log_view.scroll(-(app.log_view.page as isize));
             }
         }
-
-        KeyCode::Enter => {
-            match &app.mode {
-                Mode::Menu => app.select_mode(),
-                _ => {}
+        TuiCommand::ToggleFollow => {
+            if let Mode::Execute = app.mode {
+                app.log_view.toggle_follow();
             }
         }
-
-        KeyCode::Esc => {
-            app.mode = Mode::Menu;
+        TuiCommand::Select => {
+            if let Mode::Menu = app.mode {
+                app.select_mode();
+            }
         }
-
-        
-        // Other handlers you could add here.
+        // Leave the current screen; the menu has nothing to go back to.
+        TuiCommand::Back => app.mode = Mode::Menu,
+        TuiCommand::Quit => {
+            app.request_quit().await;
+        }
+        TuiCommand::OpenPrompt => {
+            if let Mode::Execute = app.mode {
+                app.prompt.open();
+            }
+        }
+        TuiCommand::ToggleOverlay => {
+            app.is_overlay_active = !app.is_overlay_active;
+            app.check_overlay_status().await;
+        }
+        // Other commands only apply to a specific screen.
         _ => {}
     }
     Ok(())
@@ -0,0 +1,267 @@
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::{load_app_config, parse_config, AppConfig, EffectiveConfig};
+use crate::missing_requirements;
+
+/// File extensions counted as dataset images.
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "webp"];
+
+/// File extensions counted as dataset labels.
+const LABEL_EXTENSIONS: [&str; 1] = ["txt"];
+
+/// A game directory found while scanning the workspace.
+#[derive(Debug)]
+pub struct GameEntry {
+    pub name: String,
+    pub status: GameStatus,
+}
+
+/// Whether a game directory is usable, and why not if it isn't.
+#[derive(Debug)]
+pub enum GameStatus {
+    Ready(GameInventory),
+    Broken(Vec<String>),
+}
+
+/// Contents of a game directory that passes `check_requirements`.
+#[derive(Debug)]
+pub struct GameInventory {
+    pub theme: String,
+    pub tick_rate: u64,
+    pub images: usize,
+    pub labels: usize,
+    pub models: Vec<String>,
+    pub modified: Option<DateTime<Local>>,
+}
+
+/// Scans `root` for game directories, merging each game's config with `app`.
+///
+/// Any directory holding at least one of `dataset/`, `model/` or `config.yaml`
+/// is treated as a game; the rest are ignored.
+pub fn scan_games(root: &Path, app: &AppConfig) -> io::Result<Vec<GameEntry>> {
+    let mut games = Vec::new();
+
+    if !root.exists() {
+        return Ok(games);
+    }
+
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.is_dir() || !looks_like_game(&path) {
+            continue;
+        }
+
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if !name.starts_with('.') => name.to_string(),
+            _ => continue,
+        };
+
+        let missing = missing_requirements(&path);
+        let status = if missing.is_empty() {
+            match read_inventory(&path, app) {
+                Ok(inventory) => GameStatus::Ready(inventory),
+                Err(reason) => GameStatus::Broken(vec![reason]),
+            }
+        } else {
+            GameStatus::Broken(missing)
+        };
+
+        games.push(GameEntry { name, status });
+    }
+
+    games.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(games)
+}
+
+/// Prints the games found in `root` as a table.
+pub fn print_games(root: &Path) -> io::Result<()> {
+    let app = load_app_config().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        AppConfig::default()
+    });
+    let games = scan_games(root, &app)?;
+
+    if games.is_empty() {
+        println!("No games found in '{}'.", root.display());
+        return Ok(());
+    }
+
+    let name_width = games.iter().map(|game| game.name.len()).max().unwrap_or(0).max(4);
+
+    print_row(name_width, ["GAME", "THEME", "TICK", "IMAGES", "LABELS", "MODELS", "MODIFIED"]);
+
+    for game in &games {
+        match &game.status {
+            GameStatus::Ready(inventory) => {
+                let models = if inventory.models.is_empty() {
+                    "-".to_string()
+                } else {
+                    inventory.models.join(", ")
+                };
+                let modified = inventory
+                    .modified
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "-".to_string());
+
+                print_row(
+                    name_width,
+                    [
+                        &game.name,
+                        &inventory.theme,
+                        &inventory.tick_rate.to_string(),
+                        &inventory.images.to_string(),
+                        &inventory.labels.to_string(),
+                        &models,
+                        &modified,
+                    ],
+                );
+            }
+            GameStatus::Broken(reasons) => {
+                println!("{:<name_width$}  BROKEN: {}", game.name, reasons.join("; "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints one line of the inventory table.
+fn print_row(name_width: usize, [name, theme, tick, images, labels, models, modified]: [&str; 7]) {
+    println!(
+        "{:<name_width$}  {:<10}  {:>4}  {:>6}  {:>6}  {:<24}  {}",
+        name, theme, tick, images, labels, models, modified
+    );
+}
+
+fn looks_like_game(path: &Path) -> bool {
+    path.join("dataset").exists() || path.join("model").exists() || path.join("config.yaml").exists()
+}
+
+/// Reads the config and counts the dataset and model files of a complete game directory.
+fn read_inventory(path: &Path, app: &AppConfig) -> Result<GameInventory, String> {
+    let config_file = path.join("config.yaml");
+
+    let content = fs::read_to_string(&config_file)
+        .map_err(|e| format!("config.yaml could not be read: {}", e))?;
+    let config = parse_config(&config_file, &content)
+        .map_err(|e| format!("config.yaml could not be parsed: {}", e.summary()))?
+        .config;
+    let config = EffectiveConfig::resolve(app.clone(), config);
+
+    let modified = fs::metadata(&config_file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Local>::from);
+
+    let mut models: Vec<String> = fs::read_dir(path.join("model"))
+        .map_err(|e| format!("model/ could not be read: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    models.sort();
+
+    Ok(GameInventory {
+        theme: config.app.theme.to_string(),
+        tick_rate: config.app.tick_rate,
+        images: count_files(&path.join("dataset"), &IMAGE_EXTENSIONS),
+        labels: count_files(&path.join("dataset"), &LABEL_EXTENSIONS),
+        models,
+        modified,
+    })
+}
+
+/// Recursively counts the files under `dir` with one of the given extensions.
+fn count_files(dir: &Path, extensions: &[&str]) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .map(|path| {
+            if path.is_dir() {
+                count_files(&path, extensions)
+            } else {
+                let matches = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| extensions.iter().any(|wanted| ext.eq_ignore_ascii_case(wanted)))
+                    .unwrap_or(false);
+                usize::from(matches)
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    /// Writes `content` to `path`, creating its parent directories.
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn scan_games_reports_ready_broken_and_skips_other_dirs() {
+        let root = std::env::temp_dir().join(format!("aim2go-inventory-tests-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let config = serde_yaml::to_string(&Config::default()).unwrap();
+
+        write(&root.join("valid/config.yaml"), &config);
+        write(&root.join("valid/dataset/a.png"), "");
+        write(&root.join("valid/dataset/a.txt"), "");
+        write(&root.join("valid/dataset/more/b.JPG"), "");
+        write(&root.join("valid/dataset/notes.md"), "");
+        write(&root.join("valid/model/b.onnx"), "");
+        write(&root.join("valid/model/a.onnx"), "");
+
+        write(&root.join("broken/config.yaml"), "overlay: [");
+        fs::create_dir_all(root.join("broken/dataset")).unwrap();
+        fs::create_dir_all(root.join("broken/model")).unwrap();
+
+        fs::create_dir_all(root.join("incomplete/dataset")).unwrap();
+        fs::create_dir_all(root.join("not-a-game/stuff")).unwrap();
+        write(&root.join(".hidden/config.yaml"), &config);
+        write(&root.join("readme.txt"), "");
+
+        let app = AppConfig { tick_rate: 25, ..AppConfig::default() };
+        let games = scan_games(&root, &app).unwrap();
+        let names: Vec<&str> = games.iter().map(|game| game.name.as_str()).collect();
+        assert_eq!(names, ["broken", "incomplete", "valid"]);
+
+        let GameStatus::Broken(reasons) = &games[0].status else {
+            panic!("a config that doesn't parse is broken: {:?}", games[0]);
+        };
+        assert!(matches!(&reasons[..], [reason] if reason.starts_with("config.yaml could not be parsed")), "{:?}", reasons);
+
+        let GameStatus::Broken(reasons) = &games[1].status else {
+            panic!("a game without a model dir and config is broken: {:?}", games[1]);
+        };
+        assert_eq!(reasons, &["missing 'model/' directory", "missing 'config.yaml'"]);
+
+        let GameStatus::Ready(inventory) = &games[2].status else {
+            panic!("a complete game is ready: {:?}", games[2]);
+        };
+        assert_eq!(inventory.tick_rate, 25);
+        assert_eq!(inventory.images, 2);
+        assert_eq!(inventory.labels, 1);
+        assert_eq!(inventory.models, ["a.onnx", "b.onnx"]);
+        assert!(inventory.modified.is_some());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn scanning_a_missing_root_finds_nothing() {
+        let root = std::env::temp_dir().join(format!("aim2go-inventory-missing-{}", std::process::id()));
+        assert!(scan_games(&root, &AppConfig::default()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use aim2go::config::keybind::{Key, Modifier, Modifiers, Side, PUNCTUATION};
+use aim2go::config::{KeyChord, TuiCommand};
+
+/// TUI key chords and the commands they run, built from `tui_keys`.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, TuiCommand)>,
+}
+
+impl Keymap {
+    /// Constructs a new instance of [`Keymap`].
+    ///
+    /// Chords that don't parse are skipped; loading the config already warned about them.
+    pub fn new(tui_keys: &HashMap<String, TuiCommand>) -> Self {
+        let mut bindings: Vec<(KeyChord, TuiCommand)> = tui_keys
+            .iter()
+            .filter_map(|(chord, command)| chord.parse().ok().map(|chord| (chord, *command)))
+            .collect();
+        // Short chords first so help text reads `J/DOWN` rather than `DOWN/J`.
+        bindings.sort_by_key(|(chord, _)| {
+            let chord = chord.to_string();
+            (chord.len(), chord)
+        });
+        Self { bindings }
+    }
+
+    /// Returns the command bound to a key press, if any.
+    pub fn command(&self, key_event: &KeyEvent) -> Option<TuiCommand> {
+        let pressed = chord_from_event(key_event)?;
+        let bound = |modifiers: &Modifiers| {
+            self.bindings
+                .iter()
+                .find(|(chord, _)| chord.key == pressed.key && chord.modifiers.covers(modifiers))
+                .map(|(_, command)| *command)
+        };
+
+        // Shift and Caps Lock only change the case of a letter, so `J` also answers
+        // `SHIFT+J` unless that chord is bound itself.
+        bound(&pressed.modifiers).or_else(|| match pressed.key {
+            Key::Char(c) if c.is_ascii_alphabetic() => bound(&pressed.modifiers.without(Modifier::Shift)),
+            _ => None,
+        })
+    }
+
+    /// Returns the chords bound to `command` joined by `/`, for help text.
+    pub fn chords(&self, command: TuiCommand) -> String {
+        let chords: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| *bound == command)
+            .map(|(chord, _)| chord.to_string())
+            .collect();
+        if chords.is_empty() {
+            "unbound".to_string()
+        } else {
+            chords.join("/")
+        }
+    }
+}
+
+/// Does the key press type a character when a text field has focus?
+///
+/// Text fields take these over even when they are bound, so typing `q` doesn't quit.
+pub fn types_text(key_event: &KeyEvent) -> bool {
+    matches!(key_event.code, KeyCode::Char(_)) && !key_event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Translates a terminal key press into a chord.
+///
+/// Terminals can't tell left and right modifiers apart, so only chords using plain
+/// `CTRL`, `SHIFT`, `ALT` and `WIN` can match.
+fn chord_from_event(key_event: &KeyEvent) -> Option<KeyChord> {
+    let mut shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+
+    let key = match key_event.code {
+        KeyCode::Char(' ') => Key::Space,
+        KeyCode::Char(c) if c.is_ascii_alphanumeric() || PUNCTUATION.contains(c) => {
+            // Terminals send `Q` for Shift+Q, but not always with the Shift flag set.
+            if c.is_ascii_alphabetic() {
+                shift = c.is_ascii_uppercase();
+            }
+            Key::Char(c.to_ascii_uppercase())
+        }
+        KeyCode::F(number) => Key::Function(number),
+        KeyCode::Esc => Key::Esc,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::BackTab => {
+            shift = true;
+            Key::Tab
+        }
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Insert => Key::Insert,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::CapsLock => Key::CapsLock,
+        KeyCode::PrintScreen => Key::PrintScreen,
+        KeyCode::Pause => Key::Pause,
+        KeyCode::NumLock => Key::NumLock,
+        KeyCode::ScrollLock => Key::ScrollLock,
+        KeyCode::Menu => Key::Menu,
+        _ => return None,
+    };
+
+    let mut modifiers = Modifiers::default();
+    for (held, modifier) in [
+        (key_event.modifiers.contains(KeyModifiers::CONTROL), Modifier::Ctrl),
+        (shift, Modifier::Shift),
+        (key_event.modifiers.contains(KeyModifiers::ALT), Modifier::Alt),
+        (key_event.modifiers.contains(KeyModifiers::SUPER), Modifier::Win),
+    ] {
+        if held {
+            modifiers = modifiers.with(modifier, Side::Either);
+        }
+    }
+
+    Some(KeyChord { modifiers, key })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aim2go::config::AppConfig;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn keymap(bindings: &[(&str, TuiCommand)]) -> Keymap {
+        Keymap::new(&bindings.iter().map(|(chord, command)| (chord.to_string(), *command)).collect())
+    }
+
+    #[test]
+    fn letters_match_in_either_case() {
+        let keymap = Keymap::new(&AppConfig::default().tui_keys);
+
+        for event in [
+            press(KeyCode::Char('j'), KeyModifiers::NONE),
+            // Caps Lock on
+            press(KeyCode::Char('J'), KeyModifiers::NONE),
+            // Shift held
+            press(KeyCode::Char('J'), KeyModifiers::SHIFT),
+        ] {
+            assert_eq!(keymap.command(&event), Some(TuiCommand::MoveDown), "{:?}", event);
+        }
+        assert_eq!(keymap.command(&press(KeyCode::Char('Q'), KeyModifiers::NONE)), Some(TuiCommand::Quit));
+    }
+
+    #[test]
+    fn shifted_letters_prefer_their_own_binding() {
+        let keymap = keymap(&[("J", TuiCommand::MoveDown), ("SHIFT+J", TuiCommand::PageDown)]);
+
+        assert_eq!(keymap.command(&press(KeyCode::Char('j'), KeyModifiers::NONE)), Some(TuiCommand::MoveDown));
+        assert_eq!(keymap.command(&press(KeyCode::Char('J'), KeyModifiers::SHIFT)), Some(TuiCommand::PageDown));
+    }
+
+    #[test]
+    fn modifiers_must_be_held() {
+        let keymap = Keymap::new(&AppConfig::default().tui_keys);
+
+        assert_eq!(keymap.command(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(TuiCommand::Quit));
+        assert_eq!(keymap.command(&press(KeyCode::Char('c'), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.command(&press(KeyCode::Char('s'), KeyModifiers::CONTROL)), Some(TuiCommand::Save));
+        // Shift is only ignored for letters.
+        assert_eq!(keymap.command(&press(KeyCode::Down, KeyModifiers::SHIFT)), None);
+    }
+
+    #[test]
+    fn only_plain_characters_type_text() {
+        assert!(types_text(&press(KeyCode::Char('q'), KeyModifiers::NONE)));
+        assert!(types_text(&press(KeyCode::Char('Q'), KeyModifiers::SHIFT)));
+        assert!(!types_text(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!types_text(&press(KeyCode::Esc, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn back_tab_is_shift_tab() {
+        let keymap = keymap(&[("TAB", TuiCommand::MoveDown), ("SHIFT+TAB", TuiCommand::MoveUp)]);
+
+        assert_eq!(keymap.command(&press(KeyCode::Tab, KeyModifiers::NONE)), Some(TuiCommand::MoveDown));
+        assert_eq!(keymap.command(&press(KeyCode::BackTab, KeyModifiers::NONE)), Some(TuiCommand::MoveUp));
+        assert_eq!(keymap.command(&press(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(TuiCommand::MoveUp));
+    }
+
+    #[test]
+    fn chords_list_short_chords_first() {
+        let keymap = Keymap::new(&AppConfig::default().tui_keys);
+
+        assert_eq!(keymap.chords(TuiCommand::MoveDown), "J/DOWN");
+        assert_eq!(keymap.chords(TuiCommand::Quit), "Q/CTRL+C");
+        assert_eq!(keymap.chords(TuiCommand::Select), "ENTER/SPACE");
+        assert_eq!(Keymap::default().chords(TuiCommand::Quit), "unbound");
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Align2, Color32, FontId, Painter, Pos2, Rect, Stroke};
+use aim2go::config::{Anchor, CrosshairConfig, LayerConfig, OverlayLayer};
+use aim2go::crosshair::{self, Paint, Shape};
+
+/// Space between a layer anchored to a corner and the edges of the overlay window.
+const ANCHOR_MARGIN: f32 = 16.0;
+
+/// Font size of the HUD text.
+const HUD_FONT_SIZE: f32 = 14.0;
+
+/// What the overlay knows about the session, shared by every layer.
+#[derive(Debug)]
+pub struct OverlayState {
+    pub game: String,
+    /// Start of the attach session
+    pub session_started: DateTime<Local>,
+    /// Frame rate over the last second
+    pub fps: f32,
+    pub crosshair: CrosshairConfig,
+    pub color: Color32,
+}
+
+/// Something the overlay draws.
+///
+/// The overlay places each layer at the anchor from its config, and applies its opacity
+/// to `painter`, so layers only draw their content.
+pub trait Layer {
+    /// Draws the layer at `anchor`; `align` says which side of the content touches it.
+    fn draw(&self, painter: &Painter, anchor: Pos2, align: Align2, state: &OverlayState);
+}
+
+/// Draws `layers` into `rect`, later ones on top.
+///
+/// This is everything the overlay window shows, so it can also be drawn without one.
+pub fn draw_layers(painter: &Painter, rect: Rect, layers: &[LayerConfig], state: &OverlayState) {
+    for config in layers {
+        let Some(layer) = layer(config.layer) else {
+            continue;
+        };
+        let mut painter = painter.clone();
+        painter.multiply_opacity(config.opacity);
+        let (anchor, align) = anchor_point(rect, config.anchor);
+        layer.draw(&painter, anchor, align, state);
+    }
+}
+
+/// Puts `layers` in drawing order: higher z-orders come later, so they end up on top.
+///
+/// Layers with the same z-order keep the order of the config.
+pub fn sort_layers(layers: &mut [LayerConfig]) {
+    layers.sort_by_key(|layer| layer.z_order);
+}
+
+/// Returns the layer drawing `kind`, or `None` if it has nothing to draw yet.
+pub fn layer(kind: OverlayLayer) -> Option<&'static dyn Layer> {
+    match kind {
+        OverlayLayer::Hud => Some(&Hud),
+        OverlayLayer::Crosshair => Some(&Crosshair),
+        // Both need detections, which the overlay doesn't get yet.
+        OverlayLayer::Esp | OverlayLayer::ActiveRadius => None,
+    }
+}
+
+/// Returns the point of `rect` a layer is anchored to, and how to align it there.
+pub fn anchor_point(rect: Rect, anchor: Anchor) -> (Pos2, Align2) {
+    let inner = rect.shrink(ANCHOR_MARGIN);
+    match anchor {
+        Anchor::Center => (rect.center(), Align2::CENTER_CENTER),
+        Anchor::TopLeft => (inner.left_top(), Align2::LEFT_TOP),
+        Anchor::TopRight => (inner.right_top(), Align2::RIGHT_TOP),
+        Anchor::BottomLeft => (inner.left_bottom(), Align2::LEFT_BOTTOM),
+        Anchor::BottomRight => (inner.right_bottom(), Align2::RIGHT_BOTTOM),
+    }
+}
+
+/// The crosshair, centred on its anchor.
+struct Crosshair;
+
+impl Layer for Crosshair {
+    fn draw(&self, painter: &Painter, anchor: Pos2, _align: Align2, state: &OverlayState) {
+        let config = &state.crosshair;
+        let outline = config.outline_color;
+        let outline = Color32::from_rgb(outline.0, outline.1, outline.2);
+        let fill = state.color.gamma_multiply(config.fill_opacity);
+        let at = |point: crosshair::Point| Pos2::new(anchor.x + point[0], anchor.y + point[1]);
+
+        for (paint, shape) in crosshair::shapes(config) {
+            let color = match paint {
+                Paint::Outline => outline,
+                Paint::Fill => fill,
+            };
+            match shape {
+                Shape::Disc { center, radius } => {
+                    painter.circle_filled(at(center), radius, color);
+                }
+                Shape::Ring { center, radius, width } => {
+                    // egui strokes circles on the outside of their radius.
+                    let inner = radius - width / 2.0;
+                    if inner > 0.0 {
+                        painter.circle_stroke(at(center), inner, Stroke::new(width, color));
+                    } else {
+                        painter.circle_filled(at(center), radius + width / 2.0, color);
+                    }
+                }
+                Shape::Segment { from, to, width } => {
+                    if let Some(corners) = crosshair::segment_corners(from, to, width) {
+                        let points = corners.into_iter().map(at).collect();
+                        painter.add(egui::Shape::convex_polygon(points, color, Stroke::NONE));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Game name, session time and overlay frame rate.
+struct Hud;
+
+impl Layer for Hud {
+    fn draw(&self, painter: &Painter, anchor: Pos2, align: Align2, state: &OverlayState) {
+        let elapsed = (Local::now() - state.session_started).num_seconds().max(0);
+        let text = format!(
+            "{}\n{:02}:{:02}:{:02}\n{:.0} fps",
+            state.game,
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60,
+            state.fps
+        );
+        painter.text(anchor, align, text, FontId::monospace(HUD_FONT_SIZE), state.color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render;
+
+    fn state() -> OverlayState {
+        OverlayState {
+            game: "test".to_string(),
+            session_started: Local::now(),
+            fps: 0.0,
+            crosshair: CrosshairConfig::default(),
+            color: Color32::WHITE,
+        }
+    }
+
+    #[test]
+    fn anchors_keep_a_margin_from_the_edges() {
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), egui::vec2(200.0, 100.0));
+        let anchored = |anchor| anchor_point(rect, anchor);
+
+        assert_eq!(anchored(Anchor::Center), (Pos2::new(110.0, 70.0), Align2::CENTER_CENTER));
+        assert_eq!(anchored(Anchor::TopLeft), (Pos2::new(26.0, 36.0), Align2::LEFT_TOP));
+        assert_eq!(anchored(Anchor::TopRight), (Pos2::new(194.0, 36.0), Align2::RIGHT_TOP));
+        assert_eq!(anchored(Anchor::BottomLeft), (Pos2::new(26.0, 104.0), Align2::LEFT_BOTTOM));
+        assert_eq!(anchored(Anchor::BottomRight), (Pos2::new(194.0, 104.0), Align2::RIGHT_BOTTOM));
+    }
+
+    #[test]
+    fn layers_are_sorted_by_z_order_keeping_ties_in_order() {
+        let with_z_order = |layer: OverlayLayer, z_order| LayerConfig { z_order, ..layer.default_config() };
+        let mut layers = vec![
+            with_z_order(OverlayLayer::Hud, 20),
+            with_z_order(OverlayLayer::Esp, 5),
+            with_z_order(OverlayLayer::Crosshair, -1),
+            with_z_order(OverlayLayer::ActiveRadius, 5),
+        ];
+        sort_layers(&mut layers);
+
+        let order: Vec<OverlayLayer> = layers.iter().map(|config| config.layer).collect();
+        assert_eq!(
+            order,
+            [OverlayLayer::Crosshair, OverlayLayer::Esp, OverlayLayer::ActiveRadius, OverlayLayer::Hud]
+        );
+    }
+
+    #[test]
+    fn layers_without_detections_draw_nothing() {
+        assert!(layer(OverlayLayer::Esp).is_none());
+        assert!(layer(OverlayLayer::ActiveRadius).is_none());
+        assert!(layer(OverlayLayer::Hud).is_some());
+        assert!(layer(OverlayLayer::Crosshair).is_some());
+
+        let layers = [OverlayLayer::Esp.default_config(), OverlayLayer::ActiveRadius.default_config()];
+        let image = render(32, 32, &layers, &state());
+        assert!(image.pixels.chunks_exact(4).all(|pixel| pixel[3] == 0));
+    }
+}
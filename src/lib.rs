@@ -1,40 +1,43 @@
 use tokio::fs;
 use tokio::io;
 use std::path::Path;
-use winapi::shared::windef::HWND;
-use winapi::um::winuser::{GetWindowTextW, IsWindowVisible, GetWindowTextLengthW, EnumWindows};
 use cliclack::{select, intro, outro, log::info, clear_screen, set_theme, Theme, ThemeState};
 use console::Style;
 
 pub mod config;
+pub mod crosshair;
+pub mod inventory;
+pub mod theme;
+pub mod window;
+pub mod workspace;
 
-use crate::config::{create_default_game_config, load_game_config};
+use crate::config::{create_default_app_config, create_default_game_config, EffectiveConfig};
+use crate::window::{WindowBackend, WindowHandle};
 
 #[derive(Debug)]
 struct DynamicTheme {
     pub color: Style,
+    pub accent: Style,
+    pub error: Style,
 }
 
 impl DynamicTheme {
-    pub fn from_config(game:&str) -> Self {
-        let config = load_game_config(game).expect("Failed to load config");
-
-        let theme_color = match config.app.theme.as_str() {
-            "red" => Style::new().red(),
-            "green" => Style::new().green(),
-            "blue" => Style::new().blue(),
-            "yellow" => Style::new().yellow(),
-            _ => Style::new().magenta(),
-        };
-
-        Self { color: theme_color }
-        
+    pub fn from_config(config: &EffectiveConfig) -> Self {
+        // The prompts only support the 256-colour palette.
+        let theme = &config.app.theme;
+        Self {
+            color: Style::new().color256(theme.primary.to_ansi256()),
+            accent: Style::new().color256(theme.accent.to_ansi256()),
+            error: Style::new().color256(theme.error.to_ansi256()),
+        }
     }
 }
 impl Clone for DynamicTheme {
     fn clone(&self) -> Self {
          Self {
              color: self.color.clone(),
+             accent: self.accent.clone(),
+             error: self.error.clone(),
          }
     }
 }
@@ -42,7 +45,7 @@ impl Theme for DynamicTheme {
     fn bar_color(&self, state: &ThemeState) -> Style {
         match state {
             ThemeState::Active => self.color.clone().bright(),
-            ThemeState::Error(_) => Style::new().red(),
+            ThemeState::Error(_) => self.error.clone(),
             _ => self.color.clone().dim(),
         }
     }
@@ -73,10 +76,10 @@ impl Theme for DynamicTheme {
         let label_style = if selected {
             self.color.clone()
         } else {
-            Style::new().white()
+            self.accent.clone()
         };
 
-        let hint_style = Style::new().white();
+        let hint_style = self.accent.clone();
 
         let pointer = if selected {
             format!("{}", self.color.apply_to(">>"))
@@ -99,13 +102,13 @@ impl Theme for DynamicTheme {
     }
 }
 
-/// Creates the specified directory structure.
+/// Creates the specified directory structure in the workspace root.
 pub async fn create_directory(dir_name: &str) -> io::Result<()> {
-    let base_path = Path::new(dir_name);
+    let base_path = workspace::game_dir(dir_name);
 
     // Create the main directory
-    fs::create_dir_all(base_path).await?;
-    println!("Directory '{}' created successfully!", dir_name);
+    fs::create_dir_all(&base_path).await?;
+    println!("Directory '{}' created successfully!", base_path.display());
 
     // Create 'dataset' and 'model' subdirectories
     for sub_dir in ["dataset", "model"] {
@@ -116,37 +119,20 @@ pub async fn create_directory(dir_name: &str) -> io::Result<()> {
 
     let _ = create_default_game_config(dir_name);
     println!("File 'config.yaml' created successfully!");
+
+    if let Err(e) = create_default_app_config() {
+        eprintln!("Error creating the app settings: {}", e);
+    }
     
     Ok(())
 }
 
-/// Prompts the user to select a visible window and returns the selected window's `HWND`.
-pub fn select_window(game: &str) -> Option<HWND> {
-    let mut windows: Vec<(HWND, String)> = Vec::new();
+/// Prompts the user to select a visible window and returns the selected window's handle.
+pub fn select_window(config: &EffectiveConfig, backend: &dyn WindowBackend) -> Option<WindowHandle> {
+    let windows = backend.visible_windows();
 
     // Load the theme dynamically
-    let theme = DynamicTheme::from_config(game);
-
-    // Callback to collect window handles and titles
-    unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: isize) -> i32 {
-        let windows = &mut *(lparam as *mut Vec<(HWND, String)>);
-
-        if IsWindowVisible(hwnd) != 0 {
-            let length = GetWindowTextLengthW(hwnd) as usize;
-            if length > 0 {
-                let mut buffer = vec![0u16; length + 1];
-                GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
-                let title = String::from_utf16_lossy(&buffer[..length]);
-                windows.push((hwnd, title));
-            }
-        }
-        1 // Continue enumeration
-    }
-
-    // Enumerate all visible windows
-    unsafe {
-        EnumWindows(Some(enum_windows_callback), &mut windows as *mut _ as isize);
-    }
+    let theme = DynamicTheme::from_config(config);
 
     if windows.is_empty() {
         let _ = info("No visible windows found.");
@@ -175,12 +161,12 @@ pub fn select_window(game: &str) -> Option<HWND> {
     // Show the selection menu and get the selected index
     match selector.interact() {
         Ok(selected_index) => {
-            if let Some((selected_hwnd, selected_title)) = windows.get(selected_index) {
+            if let Some((selected_handle, selected_title)) = windows.get(selected_index) {
                 let _ = info(format!(
                     "Attached to window: {}",
                     theme.color.apply_to(selected_title)
                 ));
-                Some(*selected_hwnd) // Return the HWND of the selected window
+                Some(*selected_handle) // Return the handle of the selected window
             } else {
                 let _ = info("Invalid selection.");
                 None
@@ -193,25 +179,39 @@ pub fn select_window(game: &str) -> Option<HWND> {
     }
 }
 
-/// Checks if the required directory structure exists.
+/// Checks if the required directory structure exists in the workspace root.
 pub fn check_requirements(dir_name: &str) -> bool {
-    let base_path = Path::new(dir_name);
-    base_path.is_dir()
-        && base_path.join("dataset").is_dir()
-        && base_path.join("model").is_dir()
-        && base_path.join("config.yaml").is_file()
+    missing_requirements(&workspace::game_dir(dir_name)).is_empty()
+}
+
+/// Lists the required items missing from a game directory.
+pub fn missing_requirements(base_path: &Path) -> Vec<String> {
+    if !base_path.is_dir() {
+        return vec!["directory does not exist".to_string()];
+    }
+
+    let mut missing = Vec::new();
+    for sub_dir in ["dataset", "model"] {
+        if !base_path.join(sub_dir).is_dir() {
+            missing.push(format!("missing '{}/' directory", sub_dir));
+        }
+    }
+    if !base_path.join("config.yaml").is_file() {
+        missing.push("missing 'config.yaml'".to_string());
+    }
+    missing
 }
 
 /// Deletes a directory if all required items are present.
 pub async fn check_and_delete_directory(dir_name: &str) -> io::Result<()> {
-    let base_path = Path::new(dir_name);
+    let base_path = workspace::game_dir(dir_name);
 
     if check_requirements(dir_name) {
-        println!("All required items found in '{}'. Deleting the parent directory...", dir_name);
-        fs::remove_dir_all(base_path).await?;
-        println!("Directory '{}' deleted successfully!", dir_name);
+        println!("All required items found in '{}'. Deleting the parent directory...", base_path.display());
+        fs::remove_dir_all(&base_path).await?;
+        println!("Directory '{}' deleted successfully!", base_path.display());
     } else {
-        println!("Directory '{}' does not contain all required items. No action taken.", dir_name);
+        println!("Directory '{}' does not contain all required items. No action taken.", base_path.display());
     }
 
     Ok(())
@@ -0,0 +1,518 @@
+use aim2go::config::{LogFormat, SessionLogConfig};
+use aim2go::workspace;
+use chrono::{DateTime, Local, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Most entries kept in the execution log; older ones are dropped.
+pub const LOG_CAPACITY: usize = 5000;
+
+/// Name of a session log file, without the `.log` extension.
+const SESSION_FILE_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// How often `aim2go logs --follow` checks the file for new entries.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Severity of a log entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error];
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        // Honour width and alignment so columns line up.
+        f.pad(name)
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("Unknown log level `{}`, expected debug, info, warn or error.", s)),
+        }
+    }
+}
+
+/// A line of the execution log.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Local>,
+    pub level: LogLevel,
+    /// Part of aim2go the entry comes from, e.g. `CONFIG` or `PROCESS`.
+    pub source: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Constructs a new instance of [`LogEntry`], stamped with the current time.
+    pub fn new(level: LogLevel, source: &str, message: impl Into<String>) -> Self {
+        Self {
+            timestamp: Local::now(),
+            level,
+            source: source.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} [{}] {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.level,
+            self.source,
+            self.message
+        )
+    }
+}
+
+/// Ring buffer holding the most recent [`LOG_CAPACITY`] log entries.
+#[derive(Debug)]
+pub struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::with_capacity(LOG_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    /// Constructs an empty buffer keeping at most `capacity` entries, and at least the newest one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(1024)),
+            capacity,
+        }
+    }
+
+    /// Appends an entry, dropping the oldest ones if the buffer is full.
+    pub fn push(&mut self, entry: LogEntry) {
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+}
+
+/// How the Execute screen shows the log: position, filters and search.
+#[derive(Debug)]
+pub struct LogView {
+    /// Keep the newest entries in view as they arrive?
+    pub follow: bool,
+    /// Number of matching entries between the last one shown and the newest one
+    pub scroll_back: usize,
+    /// Levels shown
+    pub levels: Vec<LogLevel>,
+    /// Only entries matching this are shown, with the matches highlighted
+    pub search: Option<Regex>,
+    /// Entries that fit on screen at the last render, used for paging
+    pub page: usize,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            follow: true,
+            scroll_back: 0,
+            levels: LogLevel::ALL.to_vec(),
+            search: None,
+            page: 1,
+        }
+    }
+}
+
+impl LogView {
+    /// Is `entry` shown with the current filters?
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.levels.contains(&entry.level)
+            && self
+                .search
+                .as_ref()
+                .is_none_or(|search| search.is_match(&entry.message) || search.is_match(&entry.source))
+    }
+
+    /// Keeps the view in place while paused and a new entry arrives.
+    pub fn entry_added(&mut self, entry: &LogEntry) {
+        if !self.follow && self.matches(entry) {
+            self.scroll_back += 1;
+        }
+    }
+
+    /// Scrolls by `lines`, towards older entries if negative.
+    ///
+    /// Scrolling up pauses the view; scrolling back down to the newest entry follows again.
+    pub fn scroll(&mut self, lines: isize) {
+        if lines < 0 {
+            self.follow = false;
+            self.scroll_back = self.scroll_back.saturating_add(lines.unsigned_abs());
+        } else {
+            self.scroll_back = self.scroll_back.saturating_sub(lines as usize);
+            self.follow |= self.scroll_back == 0;
+        }
+    }
+
+    /// Switches between following new entries and staying put.
+    pub fn toggle_follow(&mut self) {
+        self.follow = !self.follow;
+        if self.follow {
+            self.scroll_back = 0;
+        }
+    }
+
+    /// Describes the view state for the log title.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![if self.follow {
+            "follow".to_string()
+        } else {
+            format!("paused, {} newer", self.scroll_back)
+        }];
+        if self.levels.len() < LogLevel::ALL.len() {
+            let levels: Vec<String> = self.levels.iter().map(|level| level.to_string()).collect();
+            parts.push(format!("levels: {}", levels.join(" ")));
+        }
+        if let Some(search) = &self.search {
+            parts.push(format!("search: /{}/", search.as_str()));
+        }
+        parts.join(" | ")
+    }
+}
+
+/// Appends every entry logged during an attach session to `<game>/logs/<timestamp>.log`.
+#[derive(Debug)]
+pub struct SessionLog {
+    path: PathBuf,
+    file: File,
+    format: LogFormat,
+}
+
+impl SessionLog {
+    /// Starts a new session log for `game`, first deleting old ones as `config` asks.
+    pub fn create(game: &str, config: &SessionLogConfig) -> io::Result<Self> {
+        let dir = logs_dir(game);
+        fs::create_dir_all(&dir)?;
+        rotate(&session_files(&dir)?, config)?;
+
+        let (path, file) = create_session_file(&dir, &Local::now().format(SESSION_FILE_FORMAT).to_string())?;
+        Ok(Self {
+            path,
+            file,
+            format: config.format,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an entry to the file.
+    pub fn write(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let line = match self.format {
+            LogFormat::Text => entry.to_string(),
+            LogFormat::Json => serde_json::to_string(entry)?,
+        };
+        writeln!(self.file, "{}", line)
+    }
+}
+
+/// Returns the directory holding a game's logs: `<root>/<game>/logs/`
+pub fn logs_dir(game: &str) -> PathBuf {
+    workspace::game_dir(game).join("logs")
+}
+
+/// Creates `<stamp>.log` in `dir`, or `<stamp>-<n>.log` if sessions started within the
+/// same second already took that name.
+fn create_session_file(dir: &Path, stamp: &str) -> io::Result<(PathBuf, File)> {
+    for n in 0.. {
+        let file_name = if n == 0 { format!("{}.log", stamp) } else { format!("{}-{}.log", stamp, n) };
+        let path = dir.join(file_name);
+        match OpenOptions::new().append(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of session log names")
+}
+
+/// Start time and collision suffix of a session log, which order the sessions.
+fn session_order(path: &Path) -> Option<(NaiveDateTime, u32)> {
+    if path.extension()? != "log" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    if let Ok(started) = NaiveDateTime::parse_from_str(stem, SESSION_FILE_FORMAT) {
+        return Some((started, 0));
+    }
+    let (stamp, n) = stem.rsplit_once('-')?;
+    let n = n.parse().ok().filter(|&n| n > 0)?;
+    Some((NaiveDateTime::parse_from_str(stamp, SESSION_FILE_FORMAT).ok()?, n))
+}
+
+/// Returns the session logs in `dir`, oldest first.
+///
+/// Other files, such as logs saved with `/save-log`, are left out.
+pub fn session_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<((NaiveDateTime, u32), PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| Some((session_order(&path)?, path)))
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Deletes the oldest of `files` until a new session fits the limits of `config`.
+fn rotate(files: &[PathBuf], config: &SessionLogConfig) -> io::Result<()> {
+    // The session about to start takes one of the slots.
+    let keep_count = config.max_sessions.map_or(files.len(), |max| max.saturating_sub(1));
+    let mut kept_bytes = 0;
+
+    for (age, file) in files.iter().rev().enumerate() {
+        kept_bytes += fs::metadata(file)?.len();
+        let over_size = config.max_bytes.is_some_and(|max| kept_bytes > max);
+        if age >= keep_count || over_size {
+            fs::remove_file(file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a game's session log, `session` counting back from 1 for the newest.
+///
+/// With `follow`, keeps printing entries as they are appended until interrupted.
+pub async fn print_session(game: &str, session: usize, follow: bool) -> io::Result<()> {
+    let dir = logs_dir(game);
+    let files = if dir.is_dir() { session_files(&dir)? } else { Vec::new() };
+    let not_found = |message: String| io::Error::new(io::ErrorKind::NotFound, message);
+
+    if files.is_empty() {
+        return Err(not_found(format!("no session logs in '{}'", dir.display())));
+    }
+    let path = session
+        .checked_sub(1)
+        .and_then(|back| files.iter().rev().nth(back))
+        .ok_or_else(|| not_found(format!("there is no session {}, '{}' has {} session logs", session, game, files.len())))?;
+
+    let mut file = File::open(path)?;
+    let mut out = io::stdout().lock();
+    let mut pending = String::new();
+    loop {
+        let mut chunk = String::new();
+        file.read_to_string(&mut chunk)?;
+        pending.push_str(&chunk);
+
+        // Only print whole lines; a partial one is finished by a later read.
+        while let Some(end) = pending.find('\n') {
+            print_line(&mut out, &pending[..end])?;
+            pending.drain(..=end);
+        }
+
+        if !follow {
+            if !pending.is_empty() {
+                print_line(&mut out, &pending)?;
+            }
+            return Ok(());
+        }
+        out.flush()?;
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        // Pick up from the current position, starting over if the file was truncated.
+        let position = file.stream_position()?;
+        if file.metadata()?.len() < position {
+            file.seek(SeekFrom::Start(0))?;
+        }
+    }
+}
+
+/// Prints a line of a session log, decoding it first if it is JSON.
+fn print_line(out: &mut impl Write, line: &str) -> io::Result<()> {
+    match serde_json::from_str::<LogEntry>(line) {
+        Ok(entry) => writeln!(out, "{}", entry),
+        Err(_) => writeln!(out, "{}", line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(level: LogLevel, source: &str, message: &str) -> LogEntry {
+        LogEntry::new(level, source, message)
+    }
+
+    fn messages(buffer: &LogBuffer) -> Vec<&str> {
+        buffer.iter().map(|entry| entry.message.as_str()).collect()
+    }
+
+    #[test]
+    fn full_buffer_drops_the_oldest_entries() {
+        let mut buffer = LogBuffer::with_capacity(2);
+        for message in ["one", "two", "three"] {
+            buffer.push(entry(LogLevel::Info, "TEST", message));
+        }
+        assert_eq!(messages(&buffer), ["two", "three"]);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_the_newest_entry() {
+        let mut buffer = LogBuffer::with_capacity(0);
+        buffer.push(entry(LogLevel::Info, "TEST", "one"));
+        buffer.push(entry(LogLevel::Info, "TEST", "two"));
+        assert_eq!(messages(&buffer), ["two"]);
+    }
+
+    #[test]
+    fn filter_shows_only_the_chosen_levels() {
+        let view = LogView {
+            levels: vec![LogLevel::Warn, LogLevel::Error],
+            ..LogView::default()
+        };
+        assert!(view.matches(&entry(LogLevel::Error, "PROCESS", "crashed")));
+        assert!(!view.matches(&entry(LogLevel::Info, "PROCESS", "started")));
+        assert_eq!(view.describe(), "follow | levels: WARN ERROR");
+    }
+
+    #[test]
+    fn search_matches_the_message_or_the_source() {
+        let view = LogView {
+            search: Some(Regex::new("(?i)win(dow)?").unwrap()),
+            ..LogView::default()
+        };
+        assert!(view.matches(&entry(LogLevel::Info, "PROCESS", "Game window found")));
+        assert!(view.matches(&entry(LogLevel::Info, "WINDOW", "found")));
+        assert!(!view.matches(&entry(LogLevel::Info, "PROCESS", "Overlay started")));
+        assert_eq!(view.describe(), "follow | search: /(?i)win(dow)?/");
+    }
+
+    #[test]
+    fn paused_view_counts_only_new_matching_entries() {
+        let mut view = LogView {
+            levels: vec![LogLevel::Error],
+            ..LogView::default()
+        };
+        view.scroll(-1);
+        view.entry_added(&entry(LogLevel::Info, "TEST", "hidden"));
+        view.entry_added(&entry(LogLevel::Error, "TEST", "shown"));
+        assert_eq!(view.scroll_back, 2);
+        view.scroll(2);
+        assert!(view.follow);
+    }
+
+    /// An empty directory of its own for a test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aim2go-log-tests-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Creates session logs of `sizes` bytes, oldest first, a minute apart.
+    fn sessions(dir: &Path, sizes: &[usize]) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for (minute, size) in sizes.iter().enumerate() {
+            let path = dir.join(format!("20260101-10{:02}00.log", minute));
+            fs::write(&path, "x".repeat(*size)).unwrap();
+            files.push(path);
+        }
+        files
+    }
+
+    fn config(max_sessions: Option<usize>, max_bytes: Option<u64>) -> SessionLogConfig {
+        SessionLogConfig {
+            format: LogFormat::Text,
+            max_sessions,
+            max_bytes,
+        }
+    }
+
+    #[test]
+    fn rotate_leaves_room_for_the_new_session() {
+        let dir = temp_dir("max-sessions");
+        let files = sessions(&dir, &[10, 10, 10, 10]);
+
+        rotate(&files, &config(Some(3), None)).unwrap();
+        assert_eq!(session_files(&dir).unwrap(), files[2..]);
+
+        // Without a limit nothing is deleted; a limit of 0 still leaves only the new session.
+        rotate(&files[2..], &config(None, None)).unwrap();
+        assert_eq!(session_files(&dir).unwrap(), files[2..]);
+        rotate(&files[2..], &config(Some(0), None)).unwrap();
+        assert!(session_files(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rotate_keeps_the_newest_sessions_within_max_bytes() {
+        let dir = temp_dir("max-bytes");
+        let files = sessions(&dir, &[100, 10, 30, 20]);
+
+        rotate(&files, &config(None, Some(59))).unwrap();
+        assert_eq!(session_files(&dir).unwrap(), files[2..]);
+
+        rotate(&files[2..], &config(Some(10), Some(10))).unwrap();
+        assert!(session_files(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sessions_started_in_the_same_second_get_their_own_files() {
+        let dir = temp_dir("collisions");
+        fs::write(dir.join("execute-20260101-100000.log"), "saved").unwrap();
+        fs::write(dir.join("20260101-100000-x.log"), "other").unwrap();
+
+        let paths: Vec<PathBuf> = (0..11)
+            .map(|_| create_session_file(&dir, "20260101-100000").unwrap().0)
+            .collect();
+        assert_eq!(paths[0], dir.join("20260101-100000.log"));
+        assert_eq!(paths[1], dir.join("20260101-100000-1.log"));
+        assert_eq!(paths[10], dir.join("20260101-100000-10.log"));
+        // `-10` sorts after `-2` even though it doesn't as text.
+        assert_eq!(session_files(&dir).unwrap(), paths);
+    }
+
+    #[test]
+    fn text_entries_carry_the_full_date() {
+        let mut entry = entry(LogLevel::Warn, "LOG", "disk full");
+        entry.timestamp = Local.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(entry.to_string(), "2026-01-02 03:04:05 WARN  [LOG] disk full");
+    }
+}
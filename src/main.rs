@@ -1,14 +1,22 @@
 use std::io;
+use std::path::Path;
+use chrono::Local;
 use clap::Parser;
+use eframe::egui::Color32;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use aim2go::{check_and_delete_directory, create_directory, check_requirements, select_window};
+use aim2go::{check_and_delete_directory, create_directory, check_requirements, select_window, inventory::print_games};
+use aim2go::{config, window::native_backend, workspace};
+use aim2go::config::{create_default_app_config, load_game_config, migrate_game_config, update_crosshair, EffectiveConfig, OverlayLayer};
+use aim2go::crosshair::{decode_share_code, encode_share_code};
 use crate::{
     app::{App, AppResult},
     event::{Event, EventHandler},
     handler::handle_key_events,
     tui::Tui,
-    cli::{Cli, Commands},
-    config::load_game_config,
+    cli::{Cli, Commands, ConfigCommands, CrosshairCommands},
+    config::resolve_game_config,
+    log::LogLevel,
+    layer::OverlayState,
 };
 
 pub mod cli;
@@ -18,34 +26,61 @@ pub mod handler;
 pub mod thread;
 pub mod tui;
 pub mod ui;
+pub mod layer;
+pub mod render;
 pub mod overlay;
-pub mod config;
+pub mod overlay_process;
+pub mod editor;
+pub mod keymap;
+pub mod command;
+pub mod prompt;
+pub mod log;
 
 #[tokio::main] async fn main() -> AppResult<()> {
     // Parse command-line arguments
     let cli = Cli::parse();
 
+    workspace::set_root(workspace::resolve_root(cli.root.as_deref()));
+
     if cli.list {
-        println!("Feature '--list' is not implemented yet.");
+        if let Err(e) = print_games(workspace::root()) {
+            eprintln!("Error listing games: {}", e);
+        }
+        if cli.command.is_none() {
+            return Ok(());
+        }
     }
 
     match &cli.command {
         Some(Commands::New { game }) => {
-            if let Err(e) = create_directory(&game).await {
+            if let Err(e) = create_directory(game).await {
                 eprintln!("Error creating directory '{}': {}", game, e);
             }
         }
         Some(Commands::Remove { game }) => {
-            if let Err(e) = check_and_delete_directory(&game).await {
+            if let Err(e) = check_and_delete_directory(game).await {
                 eprintln!("Error removing directory '{}': {}", game, e);
             }
         }
          Some(Commands::Attach { game }) => {
-            if check_requirements(&game) {
+            if check_requirements(game) {
+                // Load the config once, reporting problems before the TUI takes over the terminal
+                let loaded = match resolve_game_config(game) {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Ok(());
+                    }
+                };
+                for warning in &loaded.warnings {
+                    eprintln!("{}", warning);
+                }
+                let config = loaded.config;
+
                 println!("Select a window to attach to...");
 
-            
-                let selected_window = select_window(&game);
+                let backend = native_backend();
+                let selected_window = select_window(&config, backend.as_ref());
                 if selected_window.is_none() {
                     eprintln!("No window selected. Exiting...");
                     return Ok(());
@@ -53,16 +88,15 @@ pub mod config;
 
                 let selected_window = selected_window.unwrap(); // Safe because we checked
 
-                let mut app = App::new(&game, selected_window);
-        
-                let config = load_game_config(&game)?;
+                let events = EventHandler::new(config.app.tick_rate);
+                let mut app = App::new(game, selected_window, backend, &config, events.app_sender());
+                for warning in &loaded.warnings {
+                    app.log_message(LogLevel::Warn, "CONFIG", warning.summary());
+                }
+                app.start_workers().await;
 
-                let tick_rate = config.app.tick_rate;
-                
                 let stdout = io::stdout();
-                let backend = CrosstermBackend::new(stdout);
-                let terminal = Terminal::new(backend)?;
-                let events = EventHandler::new(tick_rate);
+                let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
                 let mut tui = Tui::new(terminal, events);
 
                 // Initialize TUI
@@ -84,6 +118,89 @@ pub mod config;
                 println!("The specified game is missing required components. Please check and try again.");
             }
         }
+        Some(Commands::Logs { game, follow, session }) => {
+            match log::print_session(game, *session, *follow).await {
+                // The reader went away, e.g. `aim2go logs game | head`.
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+                Err(e) => eprintln!("Error reading the logs of '{}': {}", game, e),
+                Ok(()) => {}
+            }
+        }
+        Some(Commands::Overlay { game, session_start }) => {
+            if let Err(e) = overlay::run_overlay(game, *session_start) {
+                eprintln!("Error running the overlay for '{}': {}", game, e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Config { command: ConfigCommands::Migrate { game } }) => {
+            if !check_requirements(game) {
+                println!("The specified game is missing required components. Please check and try again.");
+                return Ok(());
+            }
+
+            if let Err(e) = create_default_app_config() {
+                eprintln!("Error creating the app settings: {}", e);
+            }
+
+            match migrate_game_config(game) {
+                Ok(migration) if !migration.is_needed() => {
+                    println!("Config for '{}' is already at version {}. Nothing to migrate.", game, migration.to);
+                }
+                Ok(migration) => {
+                    println!("Migrated config for '{}' from version {} to {}:", game, migration.from, migration.to);
+                    for change in &migration.changes {
+                        println!("  - {}", change);
+                    }
+                    if let Some(backup) = &migration.backup {
+                        println!("Previous config backed up to '{}'.", backup.display());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error migrating config for '{}': {}", game, e);
+                }
+            }
+        }
+        Some(Commands::Crosshair { command }) => {
+            let game = match command {
+                CrosshairCommands::Export { game }
+                | CrosshairCommands::Import { game, .. }
+                | CrosshairCommands::Preview { game, .. } => game,
+            };
+            if !check_requirements(game) {
+                println!("The specified game is missing required components. Please check and try again.");
+                return Ok(());
+            }
+
+            match command {
+                CrosshairCommands::Export { .. } => match load_game_config(game) {
+                    Ok(config) => println!("{}", encode_share_code(&config.game.overlay.crosshair)),
+                    Err(e) => eprintln!("Error exporting the crosshair of '{}': {}", game, e),
+                },
+                CrosshairCommands::Import { code, .. } => {
+                    let crosshair = match decode_share_code(code) {
+                        Ok(crosshair) => crosshair,
+                        Err(e) => {
+                            eprintln!("Error importing the crosshair: {}", e);
+                            return Ok(());
+                        }
+                    };
+                    let crosshair_type = crosshair.crosshair_type.clone();
+                    match update_crosshair(game, crosshair) {
+                        Ok(()) => println!("Imported a {:?} crosshair into the config for '{}'.", crosshair_type, game),
+                        Err(e) => eprintln!("Error importing the crosshair into '{}': {}", game, e),
+                    }
+                }
+                CrosshairCommands::Preview { out, width, height, .. } => {
+                    match resolve_game_config(game) {
+                        Ok(loaded) => match preview_crosshair(game, &loaded.config, out, *width, *height) {
+                            Ok(()) => println!("Wrote the crosshair of '{}' to '{}'.", game, out.display()),
+                            Err(e) => eprintln!("Error writing the crosshair preview to '{}': {}", out.display(), e),
+                        },
+                        Err(e) => eprintln!("{}", e),
+                    }
+                }
+            }
+        }
         None => {
             println!("No command provided. Use '--help' to see available options.");
         }
@@ -92,6 +209,27 @@ pub mod config;
     Ok(())
 }
 
+/// Renders the crosshair layer of a config to a PNG file.
+fn preview_crosshair(game: &str, config: &EffectiveConfig, out: &Path, width: u32, height: u32) -> AppResult<()> {
+    let overlay = &config.game.overlay;
+    // Drawn even while the layer is turned off, as it would be once turned on.
+    let crosshair_layer = overlay
+        .layers
+        .iter()
+        .find(|layer| layer.layer == OverlayLayer::Crosshair)
+        .cloned()
+        .unwrap_or_else(|| OverlayLayer::Crosshair.default_config());
+    let primary = config.app.theme.primary;
+    let state = OverlayState {
+        game: game.to_string(),
+        session_started: Local::now(),
+        fps: 0.0,
+        crosshair: overlay.crosshair.clone(),
+        color: Color32::from_rgb(primary.0, primary.1, primary.2),
+    };
+    render::render(width, height, &[crosshair_layer], &state).save_png(out)
+}
+
 /// Runs the TUI main loop.
 async fn run_tui<B: ratatui::backend::Backend>(
     tui: &mut Tui<B>,
@@ -106,7 +244,10 @@ async fn run_tui<B: ratatui::backend::Backend>(
             Event::Key(key_event) => handle_key_events(key_event, app).await?,
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
+            Event::App(event) => app.handle_event(event).await,
         }
+        // Pick up a tick rate changed by a config reload.
+        tui.events.set_tick_rate(app.config.app.tick_rate);
     }
     Ok(())
 }
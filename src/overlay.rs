@@ -1 +1,304 @@
+use chrono::{DateTime, Local};
+use eframe::{egui, App, NativeOptions};
+use egui::ViewportBuilder;
+use egui::{Color32, Pos2, Vec2};
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use aim2go::config::{CrosshairConfig, EffectiveConfig, LayerConfig};
+use aim2go::window::WindowRect;
 
+use crate::app::AppResult;
+use crate::event::OverlayStats;
+use crate::layer::{self, OverlayState};
+use crate::log::{LogEntry, LogLevel};
+use crate::overlay_process::{OverlayCommand, OverlayReport};
+
+/// How often the overlay repaints on its own.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the overlay reports its [`OverlayStats`].
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the overlay gets to close its window after the TUI went away.
+const ORPHAN_GRACE: Duration = Duration::from_secs(2);
+
+/// Overlay Application using `egui`
+struct OverlayApp {
+    /// Commands read from stdin
+    commands: mpsc::Receiver<OverlayCommand>,
+    /// Frames drawn since `stats_since`
+    frames: u32,
+    stats_since: Instant,
+    /// What the layers draw
+    state: OverlayState,
+    /// Is anything drawn at all?
+    visible: bool,
+    /// Layers to draw, lowest z-order first
+    layers: Vec<LayerConfig>,
+    /// Client area of the game window, or `None` while it is not on screen
+    target: Option<WindowRect>,
+    /// Target and pixels per point the window was last placed for
+    placed: Option<(WindowRect, f32)>,
+}
+
+impl OverlayApp {
+    fn new(
+        game: &str,
+        session_started: DateTime<Local>,
+        config: &EffectiveConfig,
+        commands: mpsc::Receiver<OverlayCommand>,
+    ) -> Self {
+        let mut overlay = Self {
+            commands,
+            frames: 0,
+            stats_since: Instant::now(),
+            state: OverlayState {
+                game: game.to_string(),
+                session_started,
+                fps: 0.0,
+                crosshair: CrosshairConfig::default(),
+                color: Color32::TRANSPARENT,
+            },
+            visible: true,
+            layers: Vec::new(),
+            target: None,
+            placed: None,
+        };
+        overlay.apply_config(config);
+        overlay
+    }
+
+    /// Takes over the overlay settings of a config snapshot.
+    fn apply_config(&mut self, config: &EffectiveConfig) {
+        let overlay_config = &config.game.overlay;
+        self.state.crosshair = overlay_config.crosshair.clone();
+        self.set_layers(overlay_config.layers.clone());
+
+        let primary = config.app.theme.primary;
+        self.state.color = Color32::from_rgb(primary.0, primary.1, primary.2);
+    }
+
+    fn set_layers(&mut self, mut layers: Vec<LayerConfig>) {
+        layer::sort_layers(&mut layers);
+        self.layers = layers;
+    }
+
+    /// Applies the commands received since the last frame.
+    fn apply_commands(&mut self, ctx: &egui::Context) {
+        while let Ok(command) = self.commands.try_recv() {
+            match command {
+                OverlayCommand::Configure(config) => self.apply_config(&config),
+                OverlayCommand::SetCrosshair(crosshair) => self.state.crosshair = crosshair,
+                OverlayCommand::SetColor(color) => self.state.color = Color32::from_rgb(color.0, color.1, color.2),
+                OverlayCommand::SetVisible(visible) => self.visible = visible,
+                OverlayCommand::SetLayers(layers) => self.set_layers(layers),
+                OverlayCommand::Track(target) => self.target = target,
+                OverlayCommand::Shutdown => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            }
+        }
+    }
+
+    /// Moves and sizes the window to cover the game window.
+    ///
+    /// The target is in physical pixels, but egui places the window in points of the
+    /// monitor the overlay is on, which need not be the game's. Moving there changes the
+    /// scale, so the window is placed again with the new one until both settle.
+    fn follow_target(&mut self, ctx: &egui::Context) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let scale = ctx.pixels_per_point();
+        if self.placed == Some((target, scale)) {
+            return;
+        }
+        let position = Pos2::new(target.x as f32 / scale, target.y as f32 / scale);
+        let size = Vec2::new(target.width as f32 / scale, target.height as f32 / scale);
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        self.placed = Some((target, scale));
+    }
+
+    /// Counts a drawn frame, reporting the frame rate every [`STATS_INTERVAL`].
+    fn count_frame(&mut self) {
+        self.frames += 1;
+        let elapsed = self.stats_since.elapsed();
+        if elapsed >= STATS_INTERVAL {
+            let fps = self.frames as f32 / elapsed.as_secs_f32();
+            report(&OverlayReport::Stats(OverlayStats { fps }));
+            self.state.fps = fps;
+            self.frames = 0;
+            self.stats_since = Instant::now();
+        }
+    }
+}
+
+impl App for OverlayApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.apply_commands(ctx);
+        ctx.request_repaint_after(REFRESH_INTERVAL);
+        self.count_frame();
+        self.follow_target(ctx);
+
+        egui::CentralPanel::default().frame(egui::Frame::NONE).show(ctx, |ui| {
+            let (rect, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+            // An empty window rather than a hidden one, which may stop getting frames and
+            // so never hear that the game window is back.
+            if !self.visible || self.target.is_none() {
+                return;
+            }
+
+            // Layers are sorted by z-order, so later ones end up on top.
+            layer::draw_layers(ui.painter(), rect, &self.layers, &self.state);
+        });
+    }
+
+    /// Clears to fully transparent, so only the layers show over the game.
+    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
+        [0.0; 4]
+    }
+}
+
+/// Runs the overlay window of `aim2go overlay`, the child process started by the TUI.
+///
+/// It waits for its config on stdin, then takes [`OverlayCommand`]s until told to shut
+/// down or until stdin is closed because the TUI went away.
+pub fn run_overlay(game: &str, session_started: Option<DateTime<Local>>) -> AppResult<()> {
+    let mut lines = io::stdin().lock().lines();
+    let config = match lines.next().transpose()?.map(|line| serde_json::from_str(&line)) {
+        Some(Ok(OverlayCommand::Configure(config))) => config,
+        _ => return Err("expected the overlay config as the first line on stdin".into()),
+    };
+    drop(lines);
+    // Run by hand, the session starts now.
+    let session_started = session_started.unwrap_or_else(Local::now);
+
+    let options = NativeOptions {
+        viewport: ViewportBuilder::default()
+            .with_title(format!("aim2go overlay: {}", game))
+            .with_decorations(false)  // Removes window decorations (title bar, borders)
+            .with_transparent(true)   // Enables transparency
+            .with_always_on_top()     // Keeps the overlay above all other windows
+            .with_fullscreen(false)   // Not fullscreen, just a floating overlay
+            .with_maximized(false)
+            .with_resizable(false)    // Prevents resizing
+            .with_mouse_passthrough(true), // Clicks go through to the game below
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Game Overlay",
+        options,
+        Box::new(move |cc| {
+            let (sender, commands) = mpsc::channel();
+            let ctx = cc.egui_ctx.clone();
+            thread::spawn(move || read_commands(sender, ctx));
+            report(&OverlayReport::Ready);
+            Ok(Box::new(OverlayApp::new(game, session_started, &config, commands)))
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Forwards commands from stdin to the overlay, waking it up for each one.
+fn read_commands(sender: mpsc::Sender<OverlayCommand>, ctx: egui::Context) {
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match serde_json::from_str(&line) {
+            Ok(command) => {
+                if sender.send(command).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+            Err(e) => report(&OverlayReport::Log(LogEntry::new(
+                LogLevel::Warn,
+                "OVERLAY",
+                format!("Ignored an invalid command: {}", e),
+            ))),
+        }
+    }
+
+    // The TUI is gone; close the window, and exit regardless if that hangs.
+    let _ = sender.send(OverlayCommand::Shutdown);
+    ctx.request_repaint();
+    thread::sleep(ORPHAN_GRACE);
+    std::process::exit(0);
+}
+
+/// Writes a report to stdout for the TUI.
+fn report(report: &OverlayReport) {
+    let Ok(line) = serde_json::to_string(report) else {
+        return;
+    };
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: WindowRect = WindowRect { x: 1920, y: 120, width: 1280, height: 720 };
+
+    fn overlay() -> OverlayApp {
+        let config = EffectiveConfig::resolve(Default::default(), Default::default());
+        let mut overlay = OverlayApp::new("test", Local::now(), &config, mpsc::channel().1);
+        overlay.target = Some(TARGET);
+        overlay
+    }
+
+    /// Runs a frame on a monitor of `scale`, returning the placements asked of the window.
+    fn placements(overlay: &mut OverlayApp, ctx: &egui::Context, scale: f32) -> Vec<(Pos2, Vec2)> {
+        let mut input = egui::RawInput::default();
+        input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point = Some(scale);
+        let output = ctx.run(input, |ctx| overlay.follow_target(ctx));
+        let commands = output.viewport_output.get(&egui::ViewportId::ROOT).map(|viewport| viewport.commands.clone());
+
+        let mut placements = Vec::new();
+        let mut position = None;
+        for command in commands.unwrap_or_default() {
+            match command {
+                egui::ViewportCommand::OuterPosition(pos) => position = Some(pos),
+                egui::ViewportCommand::InnerSize(size) => placements.extend(position.take().map(|pos| (pos, size))),
+                _ => {}
+            }
+        }
+        placements
+    }
+
+    #[test]
+    fn window_is_placed_again_when_the_scale_changes() {
+        let ctx = egui::Context::default();
+        let mut overlay = overlay();
+
+        // Placed from a monitor at 150%, which puts the window on the game's monitor...
+        let on_first_monitor = placements(&mut overlay, &ctx, 1.5);
+        assert_eq!(on_first_monitor, [(Pos2::new(1280.0, 80.0), Vec2::new(1280.0 / 1.5, 480.0))]);
+        assert!(placements(&mut overlay, &ctx, 1.5).is_empty());
+
+        // ...at 100%, where the same target is more points.
+        let on_game_monitor = placements(&mut overlay, &ctx, 1.0);
+        assert_eq!(on_game_monitor, [(Pos2::new(1920.0, 120.0), Vec2::new(1280.0, 720.0))]);
+        assert!(placements(&mut overlay, &ctx, 1.0).is_empty());
+    }
+
+    #[test]
+    fn window_follows_the_target() {
+        let ctx = egui::Context::default();
+        let mut overlay = overlay();
+        placements(&mut overlay, &ctx, 2.0);
+
+        overlay.target = Some(WindowRect { x: 0, y: 0, ..TARGET });
+        assert_eq!(placements(&mut overlay, &ctx, 2.0), [(Pos2::ZERO, Vec2::new(640.0, 360.0))]);
+
+        // Nothing is placed while the game window is off screen.
+        overlay.target = None;
+        assert!(placements(&mut overlay, &ctx, 2.0).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use aim2go::config::{CrosshairConfig, EffectiveConfig, LayerConfig};
+use aim2go::theme::Rgb;
+use aim2go::window::WindowRect;
+use aim2go::workspace;
+
+use crate::event::{AppEvent, OverlayStats};
+use crate::log::{LogEntry, LogLevel};
+use crate::thread::WorkerContext;
+
+/// How often the game window is checked for moves, resizes and minimising.
+const TRACK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Message from the TUI to the overlay process, one JSON object per line on its stdin.
+///
+/// The overlay exits when its stdin is closed, so it never outlives the TUI.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum OverlayCommand {
+    /// Replaces the settings the overlay draws with.
+    Configure(Box<EffectiveConfig>),
+    /// Draws another crosshair until the next `Configure`.
+    SetCrosshair(CrosshairConfig),
+    /// Draws in another colour until the next `Configure`.
+    SetColor(Rgb),
+    /// Shows or hides everything the overlay draws, leaving the window open.
+    SetVisible(bool),
+    /// Draws only these layers until the next `Configure`.
+    SetLayers(Vec<LayerConfig>),
+    /// Moves the window over the client area of the game window, or hides everything
+    /// while the game window is not on screen.
+    Track(Option<WindowRect>),
+    /// Closes the overlay window and exits.
+    Shutdown,
+}
+
+/// Message from the overlay process to the TUI, one JSON object per line on its stdout.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum OverlayReport {
+    /// The window is open.
+    Ready,
+    Stats(OverlayStats),
+    /// An entry for the execution log.
+    Log(LogEntry),
+}
+
+/// Sends [`OverlayCommand`]s to the running overlay process.
+///
+/// Each run of the overlay worker connects a channel of its own, so a run never sees
+/// commands meant for an earlier one.
+#[derive(Debug, Clone, Default)]
+pub struct OverlayControl(Arc<Mutex<Option<mpsc::UnboundedSender<OverlayCommand>>>>);
+
+impl OverlayControl {
+    /// Replaces the channel to the overlay, returning the receiving end for a new run.
+    pub fn connect(&self) -> mpsc::UnboundedReceiver<OverlayCommand> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.sender() = Some(sender);
+        receiver
+    }
+
+    /// Queues a command for the overlay; commands sent while it is not running are dropped.
+    pub fn send(&self, command: OverlayCommand) {
+        if let Some(sender) = self.sender().as_ref() {
+            let _ = sender.send(command);
+        }
+    }
+
+    fn sender(&self) -> MutexGuard<'_, Option<mpsc::UnboundedSender<OverlayCommand>>> {
+        // The guarded sender can't be left half-updated, so a poisoned lock is still usable.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Runs the overlay as a child process until it exits or the worker is cancelled.
+///
+/// Config snapshots and [`OverlayControl`] commands are forwarded to the child, and so
+/// is the geometry of the game window whenever it changes. The child's reports and stderr
+/// end up in the execution log.
+pub async fn run_overlay_process(mut context: WorkerContext) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("could not find the aim2go executable: {}", e))?;
+    let mut child = Command::new(exe)
+        .arg("--root")
+        .arg(workspace::root())
+        .args(["overlay", "--game", &context.game])
+        .arg("--session-start")
+        .arg(context.session_started.to_rfc3339())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("could not start the overlay process: {}", e))?;
+
+    let (Some(mut stdin), Some(stdout), Some(stderr)) = (child.stdin.take(), child.stdout.take(), child.stderr.take())
+    else {
+        return Err("the overlay process has no stdio pipes".to_string());
+    };
+    let mut reports = BufReader::new(stdout).lines();
+    let mut errors = BufReader::new(stderr).lines();
+
+    // Connected before the config snapshot is taken, so no change falls between the two.
+    let mut commands = context.overlay_control.connect();
+
+    let snapshot = Arc::clone(&context.config.borrow_and_update());
+    send(&mut stdin, &OverlayCommand::Configure(Box::new((*snapshot).clone()))).await?;
+    let mut stopping = false;
+
+    let mut track = tokio::time::interval(TRACK_INTERVAL);
+    track.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    // `None` until the first check, so the child always hears where to go.
+    let mut tracked: Option<Option<WindowRect>> = None;
+
+    loop {
+        tokio::select! {
+            status = child.wait() => {
+                let status = status.map_err(|e| e.to_string())?;
+                // Pass on whatever it wrote just before exiting.
+                while let Ok(Some(line)) = reports.next_line().await {
+                    handle_report(&context, &line);
+                }
+                while let Ok(Some(line)) = errors.next_line().await {
+                    context.events.send(AppEvent::Log(LogEntry::new(LogLevel::Warn, "OVERLAY", line)));
+                }
+                return if status.success() || stopping {
+                    Ok(())
+                } else {
+                    Err(format!("the overlay process ended with {}", status))
+                };
+            }
+            _ = context.token.cancelled(), if !stopping => {
+                stopping = true;
+                // If it is gone already, `wait` reports that next.
+                let _ = send(&mut stdin, &OverlayCommand::Shutdown).await;
+            }
+            Ok(()) = context.config.changed(), if !stopping => {
+                let snapshot = Arc::clone(&context.config.borrow_and_update());
+                send(&mut stdin, &OverlayCommand::Configure(Box::new((*snapshot).clone()))).await?;
+            }
+            Some(command) = commands.recv(), if !stopping => send(&mut stdin, &command).await?,
+            _ = track.tick(), if !stopping => {
+                let target = target_rect(&context);
+                if tracked != Some(target) {
+                    // Moves and resizes come in bursts while dragging; only log showing and hiding.
+                    if let Some(previous) = tracked.filter(|previous| previous.is_some() != target.is_some()) {
+                        log_tracking(&context, previous.is_none());
+                    }
+                    tracked = Some(target);
+                    send(&mut stdin, &OverlayCommand::Track(target)).await?;
+                }
+            }
+            Ok(Some(line)) = reports.next_line() => handle_report(&context, &line),
+            Ok(Some(line)) = errors.next_line() => {
+                context.events.send(AppEvent::Log(LogEntry::new(LogLevel::Warn, "OVERLAY", line)));
+            }
+        }
+    }
+}
+
+/// Returns the client area of the game window, or `None` while it is minimised, hidden
+/// or has no size.
+fn target_rect(context: &WorkerContext) -> Option<WindowRect> {
+    if !context.backend.is_visible(context.game_window) {
+        return None;
+    }
+    context
+        .backend
+        .geometry(context.game_window)
+        .filter(|rect| rect.width > 0 && rect.height > 0)
+}
+
+fn log_tracking(context: &WorkerContext, shown: bool) {
+    let message = if shown {
+        "Game window is back on screen; showing the overlay."
+    } else {
+        "Game window is not on screen; hiding the overlay."
+    };
+    context.events.send(AppEvent::Log(LogEntry::new(LogLevel::Debug, "OVERLAY", message)));
+}
+
+/// Writes a command to the overlay's stdin.
+async fn send(stdin: &mut ChildStdin, command: &OverlayCommand) -> Result<(), String> {
+    let mut line = serde_json::to_string(command).map_err(|e| e.to_string())?;
+    line.push('\n');
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("could not write to the overlay process: {}", e))
+}
+
+fn handle_report(context: &WorkerContext, line: &str) {
+    let event = match serde_json::from_str(line) {
+        Ok(OverlayReport::Ready) => AppEvent::Log(LogEntry::new(LogLevel::Debug, "OVERLAY", "Window open.")),
+        Ok(OverlayReport::Stats(stats)) => AppEvent::OverlayStats(stats),
+        Ok(OverlayReport::Log(entry)) => AppEvent::Log(entry),
+        // Anything else on stdout was not meant for us, e.g. output of a library.
+        Err(_) => AppEvent::Log(LogEntry::new(LogLevel::Debug, "OVERLAY", line)),
+    };
+    context.events.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aim2go::window::MockBackend;
+
+    const RECT: WindowRect = WindowRect { x: 100, y: 50, width: 1280, height: 720 };
+
+    #[test]
+    fn target_rect_follows_the_game_window() {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        let (context, _events, _cancel) = WorkerContext::for_test(backend.clone(), window);
+        assert_eq!(target_rect(&context), Some(RECT));
+
+        let moved = WindowRect { x: -1920, y: 0, width: 1920, height: 1080 };
+        backend.set_rect(window, moved);
+        assert_eq!(target_rect(&context), Some(moved));
+    }
+
+    #[test]
+    fn target_rect_is_none_while_the_game_window_is_off_screen() {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        let (context, _events, _cancel) = WorkerContext::for_test(backend.clone(), window);
+
+        backend.set_visible(window, false);
+        assert_eq!(target_rect(&context), None);
+
+        backend.set_visible(window, true);
+        backend.set_rect(window, WindowRect { width: 0, ..RECT });
+        assert_eq!(target_rect(&context), None);
+
+        backend.close(window);
+        assert_eq!(target_rect(&context), None);
+    }
+}
@@ -0,0 +1,267 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::command::completions;
+
+/// Most lines kept in the prompt history.
+const HISTORY_LIMIT: usize = 100;
+
+/// What a key press in the prompt asks the app to do.
+#[derive(Debug, PartialEq)]
+pub enum PromptEvent {
+    /// Nothing beyond updating the prompt.
+    None,
+    /// Run the submitted line.
+    Submit(String),
+    /// Show the possible completions of the word being typed.
+    Candidates(Vec<String>),
+}
+
+/// Byte offset of the last word of `text`, just past the whitespace before it.
+fn word_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// Single-line command prompt with history and tab completion.
+#[derive(Debug, Default)]
+pub struct Prompt {
+    /// Is the prompt taking key presses?
+    pub focused: bool,
+    /// Text typed so far
+    pub input: String,
+    /// Cursor position, in characters
+    pub cursor: usize,
+    /// Submitted lines, oldest first
+    history: Vec<String>,
+    /// Entry shown while browsing the history; `None` when editing a new line
+    history_index: Option<usize>,
+    /// Line being typed before browsing the history started
+    draft: String,
+}
+
+impl Prompt {
+    /// Focuses the prompt with a `/` already typed.
+    pub fn open(&mut self) {
+        self.focused = true;
+        self.set_input("/".to_string());
+    }
+
+    /// Unfocuses the prompt and discards what was typed.
+    pub fn close(&mut self) {
+        self.focused = false;
+        self.set_input(String::new());
+        self.history_index = None;
+    }
+
+    /// Handles a key press while the prompt is focused.
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> PromptEvent {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key_event.code {
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab => return self.complete(),
+            KeyCode::Up => self.browse_history(-1),
+            KeyCode::Down => self.browse_history(1),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            KeyCode::Char('e') if control => self.cursor = self.len(),
+            KeyCode::Char('u') if control => {
+                let at = self.byte_index(self.cursor);
+                self.input.replace_range(..at, "");
+                self.cursor = 0;
+            }
+            KeyCode::Char('w') if control => {
+                let end = self.byte_index(self.cursor);
+                let start = word_start(self.input[..end].trim_end());
+                self.cursor -= self.input[start..end].chars().count();
+                self.input.replace_range(start..end, "");
+            }
+            KeyCode::Char(c) if !control => {
+                let at = self.byte_index(self.cursor);
+                self.input.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.input.is_empty() => self.close(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let at = self.byte_index(self.cursor);
+                self.input.remove(at);
+            }
+            KeyCode::Delete if self.cursor < self.len() => {
+                let at = self.byte_index(self.cursor);
+                self.input.remove(at);
+            }
+            _ => {}
+        }
+        PromptEvent::None
+    }
+
+    fn len(&self) -> usize {
+        self.input.chars().count()
+    }
+
+    /// Byte offset of the character at `cursor`.
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.input.char_indices().nth(cursor).map_or(self.input.len(), |(i, _)| i)
+    }
+
+    fn set_input(&mut self, input: String) {
+        self.cursor = input.chars().count();
+        self.input = input;
+    }
+
+    /// Records the line in the history and hands it to the app, keeping the prompt open.
+    fn submit(&mut self) -> PromptEvent {
+        let line = self.input.trim().to_string();
+        self.set_input("/".to_string());
+        self.history_index = None;
+
+        if line.is_empty() || line == "/" {
+            return PromptEvent::None;
+        }
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
+        PromptEvent::Submit(line)
+    }
+
+    /// Steps through the history; stepping past the newest entry restores the draft.
+    fn browse_history(&mut self, direction: isize) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match (self.history_index, direction < 0) {
+            (None, true) => {
+                self.draft = self.input.clone();
+                self.history.len() - 1
+            }
+            (None, false) => return,
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) if index + 1 < self.history.len() => index + 1,
+            (Some(_), false) => {
+                self.history_index = None;
+                let draft = std::mem::take(&mut self.draft);
+                self.set_input(draft);
+                return;
+            }
+        };
+        self.history_index = Some(index);
+        self.set_input(self.history[index].clone());
+    }
+
+    /// Completes the word at the end of the line as far as it is unambiguous.
+    fn complete(&mut self) -> PromptEvent {
+        let candidates = completions(&self.input);
+        let word_start = word_start(&self.input);
+        let word = &self.input[word_start..];
+
+        let completed = match candidates.as_slice() {
+            [] => return PromptEvent::None,
+            [only] => format!("{} ", only),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.as_str(), |common, candidate| {
+                    let len = common
+                        .chars()
+                        .zip(candidate.chars())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a.len_utf8())
+                        .sum();
+                    &common[..len]
+                });
+                if common.len() <= word.len() {
+                    return PromptEvent::Candidates(candidates);
+                }
+                common.to_string()
+            }
+        };
+
+        let mut input = self.input[..word_start].to_string();
+        input.push_str(&completed);
+        self.set_input(input);
+        PromptEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(prompt: &mut Prompt, code: KeyCode) -> PromptEvent {
+        prompt.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn ctrl(prompt: &mut Prompt, c: char) -> PromptEvent {
+        prompt.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    fn prompt_with(input: &str) -> Prompt {
+        let mut prompt = Prompt {
+            focused: true,
+            ..Prompt::default()
+        };
+        prompt.set_input(input.to_string());
+        prompt
+    }
+
+    #[test]
+    fn ctrl_w_deletes_the_word_before_the_cursor() {
+        let mut prompt = prompt_with("/overlay on  ");
+        ctrl(&mut prompt, 'w');
+        assert_eq!(prompt.input, "/overlay ");
+        assert_eq!(prompt.cursor, 9);
+        ctrl(&mut prompt, 'w');
+        assert_eq!(prompt.input, "");
+        assert_eq!(prompt.cursor, 0);
+    }
+
+    #[test]
+    fn ctrl_w_handles_multi_byte_whitespace() {
+        for space in ['\u{3000}', '\u{a0}'] {
+            let mut prompt = prompt_with(&format!("/search é{}ü", space));
+            ctrl(&mut prompt, 'w');
+            assert_eq!(prompt.input, format!("/search é{}", space));
+            assert_eq!(prompt.cursor, prompt.input.chars().count());
+        }
+    }
+
+    #[test]
+    fn tab_completes_the_last_word() {
+        let mut prompt = prompt_with("/ov");
+        assert_eq!(press(&mut prompt, KeyCode::Tab), PromptEvent::None);
+        assert_eq!(prompt.input, "/overlay ");
+
+        press(&mut prompt, KeyCode::Char('o'));
+        let event = press(&mut prompt, KeyCode::Tab);
+        assert_eq!(event, PromptEvent::Candidates(vec!["on".to_string(), "off".to_string()]));
+        assert_eq!(prompt.input, "/overlay o");
+    }
+
+    #[test]
+    fn tab_handles_multi_byte_whitespace() {
+        for space in ['\u{3000}', '\u{a0}'] {
+            let mut prompt = prompt_with(&format!("/overlay{}sh", space));
+            press(&mut prompt, KeyCode::Tab);
+            assert_eq!(prompt.input, format!("/overlay{}show ", space));
+        }
+    }
+
+    #[test]
+    fn history_restores_the_draft() {
+        let mut prompt = prompt_with("/status");
+        assert_eq!(press(&mut prompt, KeyCode::Enter), PromptEvent::Submit("/status".to_string()));
+        prompt.set_input("/cl".to_string());
+        press(&mut prompt, KeyCode::Up);
+        assert_eq!(prompt.input, "/status");
+        press(&mut prompt, KeyCode::Down);
+        assert_eq!(prompt.input, "/cl");
+    }
+}
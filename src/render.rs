@@ -0,0 +1,379 @@
+use eframe::egui::{self, Color32, Pos2, Rect, Vec2};
+use egui::epaint::{ClippedPrimitive, ImageData, Primitive, Vertex};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use aim2go::config::LayerConfig;
+
+use crate::app::AppResult;
+use crate::layer::{self, OverlayState};
+
+/// An image with straight alpha, four bytes per pixel, row by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Writes the image to a PNG file.
+    pub fn save_png(&self, path: &Path) -> AppResult<()> {
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+/// Draws `layers` the way the overlay window does, in software and without a display.
+///
+/// The layers fill a transparent `width` x `height` image at one pixel per point.
+pub fn render(width: u32, height: u32, layers: &[LayerConfig], state: &OverlayState) -> RgbaImage {
+    let ctx = egui::Context::default();
+    let screen = Rect::from_min_size(Pos2::ZERO, Vec2::new(width as f32, height as f32));
+    let mut input = egui::RawInput {
+        screen_rect: Some(screen),
+        ..Default::default()
+    };
+    input.viewports.entry(input.viewport_id).or_default().native_pixels_per_point = Some(1.0);
+
+    let output = ctx.run(input, |ctx| {
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        layer::draw_layers(&painter, screen, layers, state);
+    });
+
+    let mut textures = HashMap::new();
+    for (id, delta) in output.textures_delta.set {
+        let (size, pixels): ([usize; 2], Vec<Color32>) = match &delta.image {
+            ImageData::Color(image) => (image.size, image.pixels.clone()),
+            ImageData::Font(image) => (image.size, image.srgba_pixels(None).collect()),
+        };
+        match delta.pos {
+            None => {
+                textures.insert(id, Texture { size, pixels });
+            }
+            Some(pos) => {
+                if let Some(texture) = textures.get_mut(&id) {
+                    texture.patch(pos, size, &pixels);
+                }
+            }
+        }
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    for ClippedPrimitive { clip_rect, primitive } in ctx.tessellate(output.shapes, output.pixels_per_point) {
+        let Primitive::Mesh(mesh) = primitive else {
+            continue;
+        };
+        let Some(texture) = textures.get(&mesh.texture_id) else {
+            continue;
+        };
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+            canvas.fill_triangle(clip_rect, vertices, texture);
+        }
+    }
+    canvas.into_image()
+}
+
+/// A texture egui painted with, in premultiplied sRGBA.
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+}
+
+impl Texture {
+    /// Overwrites the part of the texture at `pos` with a patch of `size`.
+    fn patch(&mut self, pos: [usize; 2], size: [usize; 2], pixels: &[Color32]) {
+        for (row, line) in pixels.chunks_exact(size[0]).enumerate() {
+            let start = (pos[1] + row) * self.size[0] + pos[0];
+            self.pixels[start..start + size[0]].copy_from_slice(line);
+        }
+    }
+
+    /// Returns the texel under a normalised texture coordinate.
+    fn sample(&self, uv: Pos2) -> Color32 {
+        let [width, height] = self.size;
+        let x = ((uv.x * width as f32) as usize).min(width - 1);
+        let y = ((uv.y * height as f32) as usize).min(height - 1);
+        self.pixels[y * width + x]
+    }
+}
+
+/// Pixels being drawn, in premultiplied sRGBA with values from 0 to 1.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+        }
+    }
+
+    /// Blends a triangle over every pixel whose centre it covers, like a GPU would.
+    ///
+    /// Colours and texture coordinates are interpolated between the vertices, which is
+    /// what gives egui's feathered edges their anti-aliasing.
+    fn fill_triangle(&mut self, clip: Rect, vertices: [&Vertex; 3], texture: &Texture) {
+        let [a, b, c] = vertices.map(|vertex| vertex.pos);
+        let area = edge(a, b, c);
+        if area == 0.0 {
+            return;
+        }
+
+        let bounds = Rect::from_points(&[a, b, c]).intersect(clip);
+        let x_range = bounds.min.x.floor().max(0.0) as u32..(bounds.max.x.ceil().max(0.0) as u32).min(self.width);
+        let y_range = bounds.min.y.floor().max(0.0) as u32..(bounds.max.y.ceil().max(0.0) as u32).min(self.height);
+        for y in y_range {
+            for x in x_range.clone() {
+                let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(center) {
+                    continue;
+                }
+                // Weights of the vertices opposite each edge, positive inside the triangle.
+                let weights = [(b, c), (c, a), (a, b)].map(|(from, to)| edge(from, to, center) / area);
+                let covered = weights.iter().zip([(b, c), (c, a), (a, b)]).all(|(weight, (from, to))| {
+                    *weight > 0.0 || (*weight == 0.0 && owns_edge(from, to, area))
+                });
+                if !covered {
+                    continue;
+                }
+
+                let uv = weights
+                    .iter()
+                    .zip(vertices)
+                    .fold(Pos2::ZERO, |uv, (weight, vertex)| uv + vertex.uv.to_vec2() * *weight);
+                let texel = texture.sample(uv).to_array();
+                let mut color = [0.0; 4];
+                for (weight, vertex) in weights.iter().zip(vertices) {
+                    for (channel, value) in color.iter_mut().zip(vertex.color.to_array()) {
+                        *channel += weight * value as f32 / 255.0;
+                    }
+                }
+                for (channel, value) in color.iter_mut().zip(texel) {
+                    *channel *= value as f32 / 255.0;
+                }
+                self.blend(x, y, color);
+            }
+        }
+    }
+
+    /// Draws a premultiplied colour over a pixel.
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        let behind = 1.0 - color[3];
+        for (channel, value) in pixel.iter_mut().zip(color) {
+            *channel = value + *channel * behind;
+        }
+    }
+
+    /// Converts to straight alpha for saving.
+    fn into_image(self) -> RgbaImage {
+        let pixels = self
+            .pixels
+            .iter()
+            .flat_map(|&[r, g, b, a]| {
+                let unmultiply = |value: f32| if a > 0.0 { value / a } else { 0.0 };
+                [unmultiply(r), unmultiply(g), unmultiply(b), a].map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect();
+        RgbaImage {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `from`, `to`, `point`.
+fn edge(from: Pos2, to: Pos2, point: Pos2) -> f32 {
+    (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x)
+}
+
+/// Decides which of two triangles sharing an edge draws the pixels exactly on it, so
+/// they are not blended twice.
+fn owns_edge(from: Pos2, to: Pos2, area: f32) -> bool {
+    // The neighbour walks the edge the other way round, and so gets the opposite answer.
+    let (dx, dy) = ((to.x - from.x) * area.signum(), (to.y - from.y) * area.signum());
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aim2go::config::{Anchor, CrosshairConfig, CrosshairPrimitive, CrosshairType, OverlayLayer};
+    use chrono::Local;
+    use std::path::PathBuf;
+
+    /// Set to rewrite the golden images from the current rendering.
+    const UPDATE_VAR: &str = "AIM2GO_UPDATE_GOLDEN";
+
+    /// Largest difference of a channel still counted as a match, to absorb rounding.
+    const TOLERANCE: u8 = 2;
+
+    fn golden_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name))
+    }
+
+    fn load_png(path: &Path) -> AppResult<RgbaImage> {
+        let mut reader = png::Decoder::new(File::open(path)?).read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(format!("{} is not 8-bit RGBA", path.display()).into());
+        }
+        pixels.truncate(info.buffer_size());
+        Ok(RgbaImage {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// Compares an image with the golden image `name`, or rewrites it if [`UPDATE_VAR`] is set.
+    fn check_golden(name: &str, actual: &RgbaImage) -> Result<(), String> {
+        let path = golden_path(name);
+        if std::env::var_os(UPDATE_VAR).is_some() {
+            actual.save_png(&path).expect("golden image is written");
+            return Ok(());
+        }
+
+        let expected = load_png(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if (expected.width, expected.height) != (actual.width, actual.height) {
+            return Err(format!(
+                "{}: expected {}x{}, rendered {}x{}",
+                path.display(),
+                expected.width,
+                expected.height,
+                actual.width,
+                actual.height
+            ));
+        }
+        let differing = expected
+            .pixels
+            .chunks_exact(4)
+            .zip(actual.pixels.chunks_exact(4))
+            .filter(|(expected, actual)| expected.iter().zip(*actual).any(|(e, a)| e.abs_diff(*a) > TOLERANCE))
+            .count();
+        if differing > 0 {
+            return Err(format!("{}: {} pixels differ", path.display(), differing));
+        }
+        Ok(())
+    }
+
+    fn assert_no_failures(failures: &[String]) {
+        assert!(
+            failures.is_empty(),
+            "rendering differs from the golden images; if the change is intended, rerun with {}=1:\n{}",
+            UPDATE_VAR,
+            failures.join("\n")
+        );
+    }
+
+    /// Every crosshair type, named after it, and a translucent one whose fill must not
+    /// show the outline through.
+    fn golden_crosshairs() -> Vec<(String, CrosshairConfig)> {
+        let mut crosshairs: Vec<(String, CrosshairConfig)> = CrosshairType::ALL
+            .into_iter()
+            .map(|crosshair_type| {
+                let mut crosshair = CrosshairConfig {
+                    crosshair_type,
+                    ..Default::default()
+                };
+                if crosshair.crosshair_type == CrosshairType::Custom {
+                    crosshair.primitives = vec![
+                        CrosshairPrimitive::Dot { center: [0.0, 0.0], radius: 1.5 },
+                        CrosshairPrimitive::Circle { center: [0.0, 0.0], radius: 10.0 },
+                        CrosshairPrimitive::Line { from: [-12.0, 14.0], to: [12.0, 14.0] },
+                    ];
+                }
+                (format!("{:?}", crosshair.crosshair_type).to_lowercase(), crosshair)
+            })
+            .collect();
+        crosshairs.push((
+            "translucent".to_string(),
+            CrosshairConfig {
+                crosshair_type: CrosshairType::Custom,
+                thickness: 4.0,
+                outline_width: 2.0,
+                fill_opacity: 0.4,
+                primitives: vec![
+                    CrosshairPrimitive::Dot { center: [-14.0, -14.0], radius: 6.0 },
+                    CrosshairPrimitive::Circle { center: [12.0, -12.0], radius: 8.0 },
+                    CrosshairPrimitive::Line { from: [-20.0, 14.0], to: [20.0, 14.0] },
+                ],
+                ..Default::default()
+            },
+        ));
+        crosshairs
+    }
+
+    fn golden_state(crosshair: CrosshairConfig) -> OverlayState {
+        OverlayState {
+            game: "golden".to_string(),
+            session_started: Local::now(),
+            fps: 0.0,
+            crosshair,
+            color: Color32::from_rgb(0, 200, 80),
+        }
+    }
+
+    fn render_crosshair(crosshair: CrosshairConfig) -> RgbaImage {
+        crosshair.validate().expect("test crosshairs are valid");
+        render(64, 64, &[OverlayLayer::Crosshair.default_config()], &golden_state(crosshair))
+    }
+
+    #[test]
+    fn crosshairs_match_golden_images() {
+        let failures: Vec<String> = golden_crosshairs()
+            .into_iter()
+            .filter_map(|(name, crosshair)| check_golden(&format!("crosshair-{}", name), &render_crosshair(crosshair)).err())
+            .collect();
+        assert_no_failures(&failures);
+    }
+
+    /// The HUD in the corners it can be anchored to, over a centred crosshair.
+    #[test]
+    fn anchored_layers_match_golden_image() {
+        let layers = [
+            OverlayLayer::Crosshair.default_config(),
+            OverlayLayer::Hud.default_config(),
+            LayerConfig { anchor: Anchor::BottomRight, opacity: 1.0, ..OverlayLayer::Hud.default_config() },
+        ];
+        let actual = render(200, 160, &layers, &golden_state(CrosshairConfig::default()));
+
+        let failures: Vec<String> = check_golden("layers-anchored", &actual).err().into_iter().collect();
+        assert_no_failures(&failures);
+    }
+
+    #[test]
+    fn shared_triangle_edges_are_drawn_once() {
+        let texture = Texture {
+            size: [1, 1],
+            pixels: vec![Color32::WHITE],
+        };
+        let vertex = |x, y| Vertex {
+            pos: Pos2::new(x, y),
+            uv: Pos2::ZERO,
+            color: Color32::from_black_alpha(128),
+        };
+        // A square split along a diagonal running through pixel centres.
+        let corners = [vertex(0.0, 0.0), vertex(8.0, 0.0), vertex(8.0, 8.0), vertex(0.0, 8.0)];
+        let mut canvas = Canvas::new(8, 8);
+        let clip = Rect::EVERYTHING;
+        canvas.fill_triangle(clip, [&corners[0], &corners[1], &corners[2]], &texture);
+        canvas.fill_triangle(clip, [&corners[0], &corners[2], &corners[3]], &texture);
+
+        let image = canvas.into_image();
+        assert!(image.pixels.chunks_exact(4).all(|pixel| pixel[3] == 128));
+    }
+}
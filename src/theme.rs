@@ -0,0 +1,309 @@
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Levels of each channel in the 6x6x6 colour cube of the xterm 256-colour palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Built-in themes, selectable by name in the `theme` setting.
+pub const PRESETS: [(&str, Theme); 6] = [
+    ("magenta", Theme::new(Rgb(255, 0, 255))),
+    ("red", Theme::new(Rgb(255, 59, 59))),
+    ("green", Theme::new(Rgb(0, 215, 95))),
+    ("blue", Theme::new(Rgb(59, 142, 234))),
+    ("yellow", Theme::new(Rgb(255, 215, 0)).with_warning(Rgb(255, 135, 0))),
+    ("cyan", Theme::new(Rgb(0, 215, 255))),
+];
+
+/// An sRGB colour, written as `#rrggbb` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Returns the closest colour of the xterm 256-colour palette, for terminals
+    /// and libraries without true colour support.
+    pub fn to_ansi256(self) -> u8 {
+        let channels = [self.0, self.1, self.2];
+        let cube_index = |value: u8| {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+                .unwrap_or(0)
+        };
+        let [r, g, b] = channels.map(cube_index);
+        let cube = Rgb(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+        // The grayscale ramp runs from 8 to 238 in steps of 10.
+        let average = channels.iter().map(|&c| c as u32).sum::<u32>() / 3;
+        let gray_index = ((average.saturating_sub(3)) / 10).min(23) as u8;
+        let gray_level = 8 + gray_index * 10;
+        let gray = Rgb(gray_level, gray_level, gray_level);
+
+        if self.distance(gray) < self.distance(cube) {
+            232 + gray_index
+        } else {
+            16 + 36 * r as u8 + 6 * g as u8 + b as u8
+        }
+    }
+
+    fn distance(self, other: Rgb) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(self.0, other.0) + d(self.1, other.1) + d(self.2, other.2)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    /// Parses `#rrggbb`, `#rgb` or `rgb(r, g, b)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!("`{}` is not a colour, expected `#rrggbb`, `#rgb` or `rgb(r, g, b)`", s);
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).map_err(|_| invalid());
+            return match hex.len() {
+                6 => Ok(Rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+                // `#f0a` is shorthand for `#ff00aa`.
+                3 => Ok(Rgb(channel(0..1)? * 17, channel(1..2)? * 17, channel(2..3)? * 17)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let inner = s
+            .strip_prefix("rgb(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let channels: Vec<u8> = inner
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        match channels[..] {
+            [r, g, b] => Ok(Rgb(r, g, b)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Colours shared by the TUI, the prompts and the overlay.
+///
+/// In the config a theme is either a preset name or a map of slots, optionally
+/// starting from a preset:
+///
+/// ```yaml
+/// theme:
+///   preset: blue
+///   accent: "#ffd700"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Text, borders and the crosshair
+    pub primary: Rgb,
+    /// Highlights, such as the logo shimmer
+    pub accent: Rgb,
+    /// Backdrop of the TUI and text drawn on `primary`
+    pub background: Rgb,
+    pub warning: Rgb,
+    pub error: Rgb,
+}
+
+impl Theme {
+    const fn new(primary: Rgb) -> Self {
+        Self {
+            primary,
+            accent: Rgb(255, 255, 255),
+            background: Rgb(0, 0, 0),
+            warning: Rgb(255, 175, 0),
+            error: Rgb(255, 95, 95),
+        }
+    }
+
+    const fn with_warning(mut self, warning: Rgb) -> Self {
+        self.warning = warning;
+        self
+    }
+
+    /// Looks up a built-in theme by name.
+    pub fn preset(name: &str) -> Option<Theme> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|(_, theme)| *theme)
+    }
+
+    /// Name of the preset this theme is identical to, if any.
+    pub fn preset_name(&self) -> Option<&'static str> {
+        PRESETS.iter().find(|(_, theme)| theme == self).map(|(name, _)| *name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        PRESETS[0].1
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.preset_name().unwrap_or("custom"))
+    }
+}
+
+/// The map form of a theme in the config.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFields {
+    preset: Option<String>,
+    primary: Option<Rgb>,
+    accent: Option<Rgb>,
+    background: Option<Rgb>,
+    warning: Option<Rgb>,
+    error: Option<Rgb>,
+}
+
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(name) = self.preset_name() {
+            return serializer.serialize_str(name);
+        }
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("primary", &self.primary)?;
+        map.serialize_entry("accent", &self.accent)?;
+        map.serialize_entry("background", &self.background)?;
+        map.serialize_entry("warning", &self.warning)?;
+        map.serialize_entry("error", &self.error)?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ThemeVisitor)
+    }
+}
+
+/// Accepts either form of a theme, keeping the deserializer's key path for errors
+/// inside the map form.
+struct ThemeVisitor;
+
+impl<'de> Visitor<'de> for ThemeVisitor {
+    type Value = Theme;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a theme name or a map of theme colours")
+    }
+
+    /// Unknown names fall back to the default theme, as they always have; loading the
+    /// config warns about them.
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Theme, E> {
+        Ok(Theme::preset(name).unwrap_or_default())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Theme, A::Error> {
+        let fields = ThemeFields::deserialize(MapAccessDeserializer::new(map))?;
+        let base = match &fields.preset {
+            Some(name) => self.visit_str(name)?,
+            None => Theme::default(),
+        };
+        Ok(Theme {
+            primary: fields.primary.unwrap_or(base.primary),
+            accent: fields.accent.unwrap_or(base.accent),
+            background: fields.background.unwrap_or(base.background),
+            warning: fields.warning.unwrap_or(base.warning),
+            error: fields.error.unwrap_or(base.error),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_colour_form() {
+        assert_eq!("#ffd700".parse(), Ok(Rgb(255, 215, 0)));
+        assert_eq!("#FFD700".parse(), Ok(Rgb(255, 215, 0)));
+        assert_eq!("#f0a".parse(), Ok(Rgb(255, 0, 170)));
+        assert_eq!("rgb(1, 2, 3)".parse(), Ok(Rgb(1, 2, 3)));
+        assert_eq!("  rgb(255,0,255) ".parse(), Ok(Rgb(255, 0, 255)));
+    }
+
+    #[test]
+    fn rejects_malformed_colours() {
+        for input in [
+            "", "#", "#ff", "#ffd7", "#ffd70g", "#ffd7000", "ffd700", "#é0", "rgb(1, 2)", "rgb(1, 2, 3, 4)",
+            "rgb(1, 2, 256)", "rgb(1, 2, -3)", "rgb(1, 2, 3", "rgb()",
+        ] {
+            assert!(input.parse::<Rgb>().is_err(), "{:?} parsed", input);
+        }
+    }
+
+    #[test]
+    fn colours_round_trip_through_display() {
+        let colour = Rgb(1, 171, 255);
+        assert_eq!(colour.to_string(), "#01abff");
+        assert_eq!(colour.to_string().parse(), Ok(colour));
+    }
+
+    #[test]
+    fn presets_are_written_by_name() {
+        for (name, theme) in PRESETS {
+            assert_eq!(serde_yaml::to_string(&theme).unwrap().trim(), name);
+            assert_eq!(serde_yaml::from_str::<Theme>(name).unwrap(), theme);
+        }
+        assert_eq!(serde_yaml::from_str::<Theme>("Yellow").unwrap(), PRESETS[4].1);
+    }
+
+    #[test]
+    fn unknown_names_fall_back_to_the_default() {
+        assert_eq!(serde_yaml::from_str::<Theme>("purple").unwrap(), Theme::default());
+    }
+
+    #[test]
+    fn maps_override_slots_of_their_preset() {
+        let theme: Theme = serde_yaml::from_str("preset: blue\naccent: '#ffd700'\n").unwrap();
+        assert_eq!(theme.primary, Rgb(59, 142, 234));
+        assert_eq!(theme.accent, Rgb(255, 215, 0));
+        assert_eq!(theme.preset_name(), None);
+
+        let theme: Theme = serde_yaml::from_str("primary: rgb(1, 2, 3)\n").unwrap();
+        assert_eq!(theme, Theme { primary: Rgb(1, 2, 3), ..Theme::default() });
+    }
+
+    #[test]
+    fn custom_themes_round_trip_as_maps() {
+        let theme = Theme { error: Rgb(1, 2, 3), ..PRESETS[2].1 };
+        let yaml = serde_yaml::to_string(&theme).unwrap();
+        assert!(yaml.contains("error: '#010203'"), "{}", yaml);
+        assert_eq!(serde_yaml::from_str::<Theme>(&yaml).unwrap(), theme);
+    }
+
+    #[test]
+    fn maps_reject_unknown_slots_and_bad_colours() {
+        assert!(serde_yaml::from_str::<Theme>("primry: '#ffffff'\n").is_err());
+        assert!(serde_yaml::from_str::<Theme>("primary: white\n").is_err());
+    }
+}
@@ -1,52 +1,602 @@
-use tokio::sync::Mutex;
+use chrono::{DateTime, Local};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::{watch, Mutex};
+use std::any::Any;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::task;
-use winapi::shared::windef::HWND;
+use tokio::time::Instant;
+use aim2go::config::EffectiveConfig;
+use aim2go::window::{WindowBackend, WindowHandle};
 
-use crate::overlay::create_overlay;
+use crate::event::{AppEvent, AppEventSender};
+use crate::overlay_process::{run_overlay_process, OverlayControl};
+
+/// How often the window watcher checks that the game window still exists.
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs a worker until it returns or its token is cancelled.
+type WorkerFn = fn(WorkerContext) -> BoxFuture<'static, Result<(), String>>;
+
+/// A running worker: its supervisor task and the switch that asks it to stop.
+#[derive(Debug)]
+struct Worker {
+    supervisor: task::JoinHandle<()>,
+    cancel: watch::Sender<bool>,
+}
+
+/// Tells a worker it has been asked to stop.
+///
+/// Workers check it regularly and return soon after it is cancelled.
+#[derive(Debug, Clone)]
+pub struct CancellationToken(watch::Receiver<bool>);
+
+impl CancellationToken {
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits until the token is cancelled, or its [`ThreadHandler`] is gone.
+    pub async fn cancelled(&mut self) {
+        let _ = self.0.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// How a request to stop a worker went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    /// The worker returned after this long.
+    Stopped(Duration),
+    /// The worker did not return in time and was aborted, killing any child process.
+    TimedOut(Duration),
+    NotRunning,
+}
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub enum ThreadType {
     GameOverlay,
+    /// Reports when the game window is closed
+    WindowWatch,
 }
 
+impl ThreadType {
+    /// Is the worker restarted after stopping on its own?
+    ///
+    /// The window watcher only stops once the game window is gone, which a restart
+    /// would just report again.
+    fn is_restartable(&self) -> bool {
+        matches!(self, ThreadType::GameOverlay)
+    }
+
+    /// Returns the function that runs this worker.
+    fn worker(&self) -> WorkerFn {
+        match self {
+            ThreadType::GameOverlay => |context| run_overlay_process(context).boxed(),
+            ThreadType::WindowWatch => |context| watch_window(context).boxed(),
+        }
+    }
+}
+
+impl fmt::Display for ThreadType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreadType::GameOverlay => f.write_str("Overlay"),
+            ThreadType::WindowWatch => f.write_str("Window watcher"),
+        }
+    }
+}
+
+/// What a worker gets to do its job.
+#[derive(Debug, Clone)]
+pub struct WorkerContext {
+    pub game: String,
+    /// When the attach session started
+    pub session_started: DateTime<Local>,
+    pub game_window: WindowHandle,
+    pub backend: Arc<dyn WindowBackend>,
+    /// Latest config snapshot
+    pub config: watch::Receiver<Arc<EffectiveConfig>>,
+    /// Publishes events to the TUI
+    pub events: AppEventSender,
+    /// Commands for the overlay process
+    pub overlay_control: OverlayControl,
+    pub token: CancellationToken,
+}
+
+/// Lifecycle of a supervised worker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerState {
+    Starting,
+    Running,
+    /// Returned without an error
+    Exited,
+    /// Returned an error
+    Failed(String),
+    Panicked(String),
+}
+
+impl WorkerState {
+    /// Did the worker stop because something went wrong?
+    pub fn is_failure(&self) -> bool {
+        matches!(self, WorkerState::Failed(_) | WorkerState::Panicked(_))
+    }
+
+    /// Has the worker stopped?
+    pub fn is_stopped(&self) -> bool {
+        !matches!(self, WorkerState::Starting | WorkerState::Running)
+    }
+}
+
+impl fmt::Display for WorkerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerState::Starting => f.write_str("starting"),
+            WorkerState::Running => f.write_str("running"),
+            WorkerState::Exited => f.write_str("exited"),
+            WorkerState::Failed(e) => write!(f, "failed: {}", e),
+            WorkerState::Panicked(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+/// A worker's change of state, reported to the app.
+#[derive(Debug, Clone)]
+pub struct WorkerStatus {
+    pub thread_type: ThreadType,
+    pub state: WorkerState,
+    /// Delay before the worker is restarted, if it stopped and will be
+    pub restart_in: Option<Duration>,
+}
+
+/// Runs workers under supervision, restarting them as `worker_restart` says.
+///
+/// Worker state changes are published as [`AppEvent::WorkerStatus`].
 #[derive(Debug)]
 pub struct ThreadHandler {
-    threads: Arc<Mutex<HashMap<ThreadType, task::JoinHandle<()>>>>,
+    threads: Arc<Mutex<HashMap<ThreadType, Worker>>>,
+    game: String,
+    session_started: DateTime<Local>,
+    /// Latest config snapshot, handed to every worker.
+    config: watch::Receiver<Arc<EffectiveConfig>>,
+    backend: Arc<dyn WindowBackend>,
+    events: AppEventSender,
+    overlay_control: OverlayControl,
 }
 
 impl ThreadHandler {
-    pub fn new() -> Self {
+    pub fn new(
+        game: &str,
+        config: watch::Receiver<Arc<EffectiveConfig>>,
+        backend: Arc<dyn WindowBackend>,
+        events: AppEventSender,
+    ) -> Self {
         Self {
             threads: Arc::new(Mutex::new(HashMap::new())),
+            game: game.to_string(),
+            session_started: Local::now(),
+            config,
+            backend,
+            events,
+            overlay_control: OverlayControl::default(),
         }
     }
-    
-    pub async fn start_thread(&self, game_window: HWND, thread_type: ThreadType) {
-    let threads = Arc::clone(&self.threads);
-    let hwnd_value = game_window as usize; // Convert HWND to usize
 
-    let handle = tokio::task::spawn_blocking(move || {
-        let game_hwnd = hwnd_value as HWND; // Convert usize back to HWND
+    /// Returns the sender workers and other background tasks publish [`AppEvent`]s through.
+    pub fn events(&self) -> AppEventSender {
+        self.events.clone()
+    }
+
+    /// Returns the channel into the game overlay, whichever run of it is current.
+    pub fn overlay_control(&self) -> &OverlayControl {
+        &self.overlay_control
+    }
+
+    pub async fn start_thread(&self, game_window: WindowHandle, thread_type: ThreadType) {
+        self.start_worker(game_window, thread_type.clone(), thread_type.worker()).await;
+    }
+
+    /// Starts `worker` under supervision as the worker of `thread_type`.
+    async fn start_worker(&self, game_window: WindowHandle, thread_type: ThreadType, worker: WorkerFn) {
+        // Replace any worker of the same type; if it is stuck, it is aborted.
+        self.stop_thread(&thread_type).await;
+
+        let (cancel, token) = watch::channel(false);
+        let supervisor = Supervisor {
+            thread_type: thread_type.clone(),
+            worker,
+            context: WorkerContext {
+                game: self.game.clone(),
+                session_started: self.session_started,
+                game_window,
+                backend: Arc::clone(&self.backend),
+                config: self.config.clone(),
+                events: self.events.clone(),
+                overlay_control: self.overlay_control.clone(),
+                token: CancellationToken(token),
+            },
+        };
+        let worker = Worker {
+            supervisor: tokio::spawn(supervisor.run()),
+            cancel,
+        };
+
+        self.threads.lock().await.insert(thread_type, worker);
+    }
+
+    /// Asks a worker to stop and waits for it to return, at most `worker_stop_timeout_ms`.
+    pub async fn stop_thread(&self, thread_type: &ThreadType) -> StopOutcome {
+        let Some(mut worker) = self.threads.lock().await.remove(thread_type) else {
+            return StopOutcome::NotRunning;
+        };
+        if worker.supervisor.is_finished() {
+            return StopOutcome::NotRunning;
+        }
+
+        let timeout = Duration::from_millis(self.config.borrow().app.worker_stop_timeout_ms);
+        let started = Instant::now();
+        let _ = worker.cancel.send(true);
 
-        match thread_type {
-            ThreadType::GameOverlay => {
-                create_overlay(game_hwnd); // Now using valid HWND safely
+        match tokio::time::timeout(timeout, &mut worker.supervisor).await {
+            Ok(_) => StopOutcome::Stopped(started.elapsed()),
+            Err(_) => {
+                // The supervisor runs the worker inline, so aborting it drops the worker and
+                // with it any child process, which is started with `kill_on_drop`.
+                worker.supervisor.abort();
+                let _ = worker.supervisor.await;
+                StopOutcome::TimedOut(timeout)
             }
         }
-    });
+    }
+}
+
+/// Starts a worker and watches it, restarting it when it stops.
+struct Supervisor {
+    thread_type: ThreadType,
+    worker: WorkerFn,
+    context: WorkerContext,
+}
+
+impl Supervisor {
+    async fn run(mut self) {
+        let mut restarts = 0;
+
+        loop {
+            self.report(WorkerState::Starting, None);
+            let started = Instant::now();
+            let worker = (self.worker)(self.context.clone());
+            self.report(WorkerState::Running, None);
+
+            // Run inline rather than spawned, so aborting the supervisor drops the worker.
+            let state = match SUPERVISED.scope((), AssertUnwindSafe(worker).catch_unwind()).await {
+                Ok(Ok(())) => WorkerState::Exited,
+                Ok(Err(e)) => WorkerState::Failed(e),
+                Err(payload) => WorkerState::Panicked(panic_message(payload)),
+            };
+            // Stopping on request is reported by `stop_thread`, not as a state change.
+            if self.context.token.is_cancelled() {
+                return;
+            }
+
+            // Read the policy now so edits apply to the next restart.
+            let restart = self.context.config.borrow().app.worker_restart.clone();
+            // A worker that stayed up for a while starts the backoff over.
+            if started.elapsed() >= Duration::from_millis(restart.max_backoff_ms) {
+                restarts = 0;
+            }
+            let delay = if self.thread_type.is_restartable() {
+                restart.delay(state.is_failure(), restarts)
+            } else {
+                None
+            };
+            self.report(state, delay);
 
-    let mut map = threads.lock().await;
-    map.insert(thread_type, handle);
-}    
-    pub async fn stop_thread(&self, thread_type: &ThreadType) {
-        let mut threads = self.threads.lock().await;
-        if let Some(handle) = threads.remove(thread_type) {
-            handle.abort();
+            let Some(delay) = delay else {
+                return;
+            };
+            restarts += 1;
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = self.context.token.cancelled() => return,
+            }
         }
     }
 
+    fn report(&self, state: WorkerState, restart_in: Option<Duration>) {
+        self.context.events.send(AppEvent::WorkerStatus(WorkerStatus {
+            thread_type: self.thread_type.clone(),
+            state,
+            restart_in,
+        }));
+    }
 }
 
+/// Publishes [`AppEvent::WindowLost`] and fails once the game window is closed.
+async fn watch_window(mut context: WorkerContext) -> Result<(), String> {
+    loop {
+        if !context.backend.is_alive(context.game_window) {
+            context.events.send(AppEvent::WindowLost(context.game_window));
+            return Err("the game window was closed".to_string());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(WINDOW_POLL_INTERVAL) => {}
+            _ = context.token.cancelled() => return Ok(()),
+        }
+    }
+}
+
+tokio::task_local! {
+    /// Set while a supervisor runs its worker.
+    static SUPERVISED: ();
+}
+
+/// Is the current thread running a supervised worker, whose panics are caught and reported
+/// in the execution log?
+pub fn is_supervised() -> bool {
+    SUPERVISED.try_with(|_| ()).is_ok()
+}
+
+/// Extracts the message a worker panicked with.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+impl WorkerContext {
+    /// Creates a context for a worker on `game_window`, returning the events it publishes
+    /// and the switch that cancels it.
+    pub fn for_test(
+        backend: Arc<dyn WindowBackend>,
+        game_window: WindowHandle,
+    ) -> (Self, tokio::sync::mpsc::UnboundedReceiver<crate::event::Event>, watch::Sender<bool>) {
+        let (events, received) = AppEventSender::channel();
+        let (cancel, token) = watch::channel(false);
+        let (_, config) = watch::channel(Arc::new(EffectiveConfig::resolve(Default::default(), Default::default())));
+        let context = WorkerContext {
+            game: "test".to_string(),
+            session_started: Local::now(),
+            game_window,
+            backend,
+            config,
+            events,
+            overlay_control: OverlayControl::default(),
+            token: CancellationToken(token),
+        };
+        (context, received, cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aim2go::config::{AppConfig, Config, RestartConfig, RestartPolicy};
+    use aim2go::window::{MockBackend, WindowRect};
+    use crate::event::Event;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const RECT: WindowRect = WindowRect { x: 0, y: 0, width: 800, height: 600 };
+
+    #[tokio::test]
+    async fn only_workers_count_as_supervised() {
+        assert!(!is_supervised());
+        let supervised = SUPERVISED.scope((), async {
+            task::yield_now().await;
+            is_supervised()
+        });
+        assert!(supervised.await);
+        assert!(!is_supervised());
+    }
+
+    #[tokio::test]
+    async fn watch_window_reports_a_closed_window() {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        backend.close(window);
+        let (context, mut events, _cancel) = WorkerContext::for_test(backend, window);
+
+        assert!(watch_window(context).await.is_err());
+        assert!(matches!(events.try_recv(), Ok(Event::App(AppEvent::WindowLost(lost))) if lost == window));
+    }
+
+    #[tokio::test]
+    async fn watch_window_returns_quietly_when_cancelled() {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        let (context, mut events, cancel) = WorkerContext::for_test(backend, window);
+        cancel.send(true).unwrap();
+
+        assert_eq!(watch_window(context).await, Ok(()));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn window_watcher_is_not_restarted_after_the_window_closed() {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        backend.close(window);
+
+        let mut app = AppConfig::default();
+        app.worker_restart.policy = RestartPolicy::Always;
+        app.worker_restart.backoff_ms = 1;
+        let (_config_sender, config) = watch::channel(Arc::new(EffectiveConfig::resolve(app, Config::default())));
+        let (events, mut received) = AppEventSender::channel();
+        let handler = ThreadHandler::new("test", config, backend, events);
+        handler.start_thread(window, ThreadType::WindowWatch).await;
+
+        let mut lost = 0;
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), received.recv()).await;
+            match event.expect("the watcher stops within 5s").expect("the handler is alive") {
+                Event::App(AppEvent::WindowLost(_)) => lost += 1,
+                Event::App(AppEvent::WorkerStatus(status)) if status.state.is_stopped() => {
+                    assert_eq!(status.restart_in, None);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(lost, 1);
+    }
+
+    /// A handler with a single game window, whose workers run under `app`'s settings.
+    fn handler_with(app: AppConfig) -> (ThreadHandler, tokio::sync::mpsc::UnboundedReceiver<Event>, WindowHandle) {
+        let backend = Arc::new(MockBackend::new());
+        let window = backend.add_window("Game", RECT);
+        let (_, config) = watch::channel(Arc::new(EffectiveConfig::resolve(app, Config::default())));
+        let (events, received) = AppEventSender::channel();
+        (ThreadHandler::new("test", config, backend, events), received, window)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stopping_a_worker_that_honours_its_token() {
+        let (handler, _events, window) = handler_with(AppConfig::default());
+        handler
+            .start_worker(window, ThreadType::GameOverlay, |mut context| {
+                async move {
+                    context.token.cancelled().await;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Ok(())
+                }
+                .boxed()
+            })
+            .await;
+
+        let outcome = handler.stop_thread(&ThreadType::GameOverlay).await;
+        assert_eq!(outcome, StopOutcome::Stopped(Duration::from_millis(100)));
+        assert_eq!(handler.stop_thread(&ThreadType::GameOverlay).await, StopOutcome::NotRunning);
+    }
 
+    #[tokio::test(start_paused = true)]
+    async fn stopping_a_worker_that_ignores_its_token_aborts_it() {
+        static DROPPED: AtomicBool = AtomicBool::new(false);
+        struct DropFlag;
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                DROPPED.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let app = AppConfig { worker_stop_timeout_ms: 250, ..AppConfig::default() };
+        let (handler, _events, window) = handler_with(app);
+        handler
+            .start_worker(window, ThreadType::GameOverlay, |_| {
+                async {
+                    let _flag = DropFlag;
+                    std::future::pending::<()>().await;
+                    Ok(())
+                }
+                .boxed()
+            })
+            .await;
+
+        let outcome = handler.stop_thread(&ThreadType::GameOverlay).await;
+        assert_eq!(outcome, StopOutcome::TimedOut(Duration::from_millis(250)));
+        assert!(DROPPED.load(Ordering::SeqCst), "the worker was dropped");
+        assert_eq!(handler.stop_thread(&ThreadType::GameOverlay).await, StopOutcome::NotRunning);
+    }
+
+    /// Starts `worker` as the overlay with `restart` as its restart settings.
+    async fn supervise(restart: RestartConfig, worker: WorkerFn) -> (ThreadHandler, tokio::sync::mpsc::UnboundedReceiver<Event>) {
+        let app = AppConfig { worker_restart: restart, ..AppConfig::default() };
+        let (handler, events, window) = handler_with(app);
+        handler.start_worker(window, ThreadType::GameOverlay, worker).await;
+        (handler, events)
+    }
+
+    /// Waits for the next `count` times the worker stops, with the restart delay of each.
+    async fn stops(
+        events: &mut tokio::sync::mpsc::UnboundedReceiver<Event>,
+        count: usize,
+    ) -> Vec<(WorkerState, Option<Duration>)> {
+        let mut stops = Vec::new();
+        while stops.len() < count {
+            let event = tokio::time::timeout(Duration::from_secs(60), events.recv()).await;
+            if let Event::App(AppEvent::WorkerStatus(status)) = event.expect("the worker stops").expect("the handler is alive") {
+                if status.state.is_stopped() {
+                    stops.push((status.state, status.restart_in));
+                }
+            }
+        }
+        stops
+    }
+
+    fn restart(policy: RestartPolicy, max_restarts: Option<u32>) -> RestartConfig {
+        RestartConfig { policy, backoff_ms: 100, max_backoff_ms: 1_000, max_restarts }
+    }
+
+    fn after(ms: u64) -> Option<Duration> {
+        Some(Duration::from_millis(ms))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn never_leaves_a_failed_worker_stopped() {
+        let (_handler, mut events) = supervise(restart(RestartPolicy::Never, None), |_| {
+            async { Err("boom".to_string()) }.boxed()
+        })
+        .await;
+
+        assert_eq!(stops(&mut events, 1).await, [(WorkerState::Failed("boom".to_string()), None)]);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(events.try_recv().is_err(), "the worker was not restarted");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn on_failure_restarts_failed_workers_with_growing_backoff() {
+        let (_handler, mut events) = supervise(restart(RestartPolicy::OnFailure, Some(3)), |_| {
+            async { panic!("boom") }.boxed()
+        })
+        .await;
+
+        let panicked = WorkerState::Panicked("boom".to_string());
+        assert_eq!(
+            stops(&mut events, 4).await,
+            [
+                (panicked.clone(), after(100)),
+                (panicked.clone(), after(200)),
+                (panicked.clone(), after(400)),
+                (panicked, None),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn on_failure_leaves_an_exited_worker_stopped() {
+        let (_handler, mut events) = supervise(restart(RestartPolicy::OnFailure, None), |_| async { Ok(()) }.boxed()).await;
+
+        assert_eq!(stops(&mut events, 1).await, [(WorkerState::Exited, None)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn always_restarts_exited_workers_up_to_the_longest_backoff() {
+        let (_handler, mut events) = supervise(restart(RestartPolicy::Always, None), |_| async { Ok(()) }.boxed()).await;
+
+        let delays: Vec<_> = stops(&mut events, 6).await.into_iter().map(|(_, delay)| delay).collect();
+        assert_eq!(delays, [after(100), after(200), after(400), after(800), after(1_000), after(1_000)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backoff_starts_over_after_a_long_run() {
+        let (_handler, mut events) = supervise(restart(RestartPolicy::OnFailure, Some(2)), |context| {
+            async move {
+                let ran_for = context.config.borrow().app.worker_restart.max_backoff_ms;
+                tokio::time::sleep(Duration::from_millis(ran_for)).await;
+                Err("boom".to_string())
+            }
+            .boxed()
+        })
+        .await;
+
+        // Never more than one restart in a row, so neither the delay nor `max_restarts` add up.
+        let delays: Vec<_> = stops(&mut events, 4).await.into_iter().map(|(_, delay)| delay).collect();
+        assert_eq!(delays, [after(100); 4]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::app::{App, AppResult};
 use crate::event::EventHandler;
+use crate::thread;
 use crate::ui;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
@@ -35,8 +36,13 @@ impl<B: Backend> Tui<B> {
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
+        // Panics in supervised workers are caught and reported in the execution log while
+        // the interface keeps running, so they neither reset the terminal nor print over it.
         let panic_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic| {
+            if thread::is_supervised() {
+                return;
+            }
             Self::reset().expect("failed to reset the terminal");
             panic_hook(panic);
         }));
@@ -1,62 +1,87 @@
 use ratatui::{
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Text, Line, Span},
-    widgets::{Paragraph, Block, Borders, BorderType, block::Position, List,  ListItem},
+    widgets::{Paragraph, Block, Borders, BorderType, block::Position, List,  ListItem, ListState},
     layout::{Alignment, Constraint, Direction, Layout},
     Frame,
 };
 use crate::app::{App, Mode};
-use crate::config::load_game_config;
-
-/// Returns the corresponding theme color from config
-fn get_theme_color(game: &str) -> Color {
-    let config = load_game_config(game).unwrap_or_else(|_| panic!("Failed to load config"));
-
-    match config.app.theme.as_str() {
-        "red" => Color::Red,
-        "green" => Color::Green,
-        "blue" => Color::Blue,
-        "yellow" => Color::Yellow,
-        _ => Color::Magenta,
+use aim2go::theme::{Rgb, Theme};
+use crate::editor::{Row, Status};
+use crate::log::{LogEntry, LogLevel};
+use crate::thread::ThreadType;
+use regex::Regex;
+use aim2go::config::TuiCommand;
+
+/// Converts a theme colour for the terminal.
+fn color(rgb: Rgb) -> Color {
+    Color::Rgb(rgb.0, rgb.1, rgb.2)
+}
+
+/// Renders a log entry coloured by level, with the search matches highlighted.
+fn log_line<'a>(entry: &'a LogEntry, theme: &Theme, search: Option<&Regex>) -> Line<'a> {
+    let style = match entry.level {
+        LogLevel::Debug => Style::default().fg(color(theme.primary)).add_modifier(Modifier::DIM),
+        LogLevel::Info => Style::default().fg(color(theme.primary)),
+        LogLevel::Warn => Style::default().fg(color(theme.warning)),
+        LogLevel::Error => Style::default().fg(color(theme.error)),
+    };
+    let highlight = Style::default().fg(color(theme.background)).bg(color(theme.accent));
+
+    let mut spans = vec![
+        Span::styled(entry.timestamp.format("%H:%M:%S ").to_string(), style.add_modifier(Modifier::DIM)),
+        Span::styled(format!("{:<5} [{}] ", entry.level, entry.source), style),
+    ];
+    let mut last = 0;
+    for found in search.into_iter().flat_map(|search| search.find_iter(&entry.message)) {
+        spans.push(Span::styled(&entry.message[last..found.start()], style));
+        spans.push(Span::styled(found.as_str(), highlight));
+        last = found.end();
     }
+    spans.push(Span::styled(&entry.message[last..], style));
+    Line::from(spans)
 }
 
 /// Generates a single diagonal gradient across the entire ASCII art.
+///
+/// Neighbouring characters of the same colour share a span.
 pub fn gradient_line(
     text: &str,
     row_index: usize,
     center: usize,
-    game: &str,
+    base_color: Color,
     gradient: &[Color],
 ) -> Line<'static> {
-    let gradient_len = gradient.len();
-    let mut spans = Vec::with_capacity(text.len());
-
-    let theme_color = get_theme_color(&game);
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_color = base_color;
 
     for (col_index, ch) in text.chars().enumerate() {
-        let diagonal_position = row_index + col_index;
-        let distance = diagonal_position as isize - center as isize;
-
-        let color = if distance >= 0 && (distance as usize) < gradient_len {
-            gradient[distance as usize]
-        } else {
-            theme_color
-        };
-
-        spans.push(Span::styled(
-            ch.to_string(),
-            Style::default().fg(color),
-        ));
+        let distance = (row_index + col_index) as isize - center as isize;
+        let color = usize::try_from(distance)
+            .ok()
+            .and_then(|distance| gradient.get(distance))
+            .copied()
+            .unwrap_or(base_color);
+
+        if color != run_color && !run.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut run), Style::default().fg(run_color)));
+        }
+        run_color = color;
+        run.push(ch);
+    }
+
+    if !run.is_empty() {
+        spans.push(Span::styled(run, Style::default().fg(run_color)));
     }
 
     Line::from(spans)
 }
 
 /// Checks if the terminal size is sufficient, otherwise shows a resize popup.
-pub fn check_terminal_size(frame: &mut Frame, game: &str, required_height: usize, required_width: usize) -> bool {
+pub fn check_terminal_size(frame: &mut Frame, theme: &Theme, required_height: usize, required_width: usize) -> bool {
     let terminal_size = frame.area();
-    let color = get_theme_color(game);
+    let warning_color = color(theme.warning);
     if (terminal_size.height as usize) < required_height || (terminal_size.width as usize) < required_width {
         let popup = Paragraph::new("Please resize the terminal to view the full content.")
             .alignment(Alignment::Center)
@@ -66,7 +91,7 @@ pub fn check_terminal_size(frame: &mut Frame, game: &str, required_height: usize
                 .title("Warning")
                 .title_position(Position::Top)
                 .title_alignment(Alignment::Center)
-                .style(Style::default().fg(color)));
+                .style(Style::default().fg(warning_color)));
         
         let popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -81,6 +106,8 @@ pub fn check_terminal_size(frame: &mut Frame, game: &str, required_height: usize
 
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame) {
+    let theme = app.config.app.theme;
+
     match &app.mode {
         Mode::Menu => {
             let required_logo_height = app.logo.lines().count();
@@ -88,7 +115,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             let required_height = required_logo_height + required_select_height + 35;
             let required_width = app.logo.lines().map(|line| line.len()).max().unwrap_or(0) + 10;
 
-            if !check_terminal_size(frame, &app.game, required_height, required_width) {
+            if !check_terminal_size(frame, &theme, required_height, required_width) {
                 return;
             }
 
@@ -112,7 +139,9 @@ pub fn render(app: &mut App, frame: &mut Frame) {
                 ])
                 .split(outer_layout[2]);
 
-            let theme_color = get_theme_color(&app.game);
+            let theme_color = color(theme.primary);
+            let accent_color = color(theme.accent);
+            let background_color = color(theme.background);
     
             let gradient = vec![
                 theme_color,
@@ -121,25 +150,25 @@ pub fn render(app: &mut App, frame: &mut Frame) {
                 theme_color,
                 theme_color,
                 theme_color,
-                Color::White,
-                Color::White,
-                Color::White,
-                Color::White,
-                Color::White,
-                Color::White,
+                accent_color,
+                accent_color,
+                accent_color,
+                accent_color,
+                accent_color,
+                accent_color,
             ];
 
             let gradient_lines: Vec<Line> = app.logo
                 .lines()
                 .enumerate()
                 .map(|(row_index, line)| {
-                    gradient_line(line, row_index, app.logo_gradient, &app.game, &gradient)
+                    gradient_line(line, row_index, app.logo_gradient, theme_color, &gradient)
                 })
                 .collect();
 
             let ascii = Paragraph::new(gradient_lines)
                 .alignment(Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(background_color));
 
             frame.render_widget(ascii, outer_layout[0]);
 
@@ -157,7 +186,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     
             let list = List::new(list_items)
                 .style(Style::default().fg(theme_color))
-                .highlight_style(Style::default().fg(Color::Black).bg(theme_color))
+                .highlight_style(Style::default().fg(background_color).bg(theme_color))
                 .highlight_symbol("/");
 
             frame.render_stateful_widget(list, inner_layout[1], &mut app.menu_state);      
@@ -171,23 +200,119 @@ pub fn render(app: &mut App, frame: &mut Frame) {
                 ])
                 .split(frame.area());
 
-            let theme_color = get_theme_color(&app.game);
+            let theme_color = color(theme.primary);
+
+            // Show the page of matching entries ending `scroll_back` entries before the newest.
+            let shown: Vec<&LogEntry> = app.log.iter().filter(|entry| app.log_view.matches(entry)).collect();
+            let page = layout[0].height.saturating_sub(2).max(1) as usize;
+            app.log_view.page = page;
+            app.log_view.scroll_back = app.log_view.scroll_back.min(shown.len().saturating_sub(page));
+            let end = shown.len() - app.log_view.scroll_back;
+            let start = end.saturating_sub(page);
 
-            let log_lines: Vec<Line> = app
-                .log
+            let log_lines: Vec<Line> = shown[start..end]
                 .iter()
-                .map(|entry| Line::from(Span::styled(entry, Style::default().fg(theme_color))))
+                .map(|entry| log_line(entry, &theme, app.log_view.search.as_ref()))
                 .collect();
 
+            let title = format!("[Execution Log] ({})", app.log_view.describe());
+            let workers = format!("[{}: {}]", ThreadType::GameOverlay, app.worker_description(&ThreadType::GameOverlay));
             let log = Paragraph::new(Text::from(log_lines))
-                .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title("[Execution Log]").title_alignment(Alignment::Center).border_style(Style::default().fg(theme_color)));
+                .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).title_alignment(Alignment::Center).title_bottom(Line::from(workers).right_aligned()).border_style(Style::default().fg(theme_color)));
 
             frame.render_widget(log, layout[0]);
 
-            let input = Paragraph::new("/help or 'h' for keybind/commands").block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(theme_color))).style(Style::default().fg(theme_color));
+            let prompt_text = if app.prompt.focused {
+                app.prompt.input.clone()
+            } else {
+                format!("Press {} to type a command, /help to list them", app.keymap.chords(TuiCommand::OpenPrompt))
+            };
+
+            let input = Paragraph::new(prompt_text).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(theme_color))).style(Style::default().fg(theme_color));
 
             frame.render_widget(input, layout[1]);
 
+            if app.prompt.focused {
+                let cursor_x = layout[1].x + 1 + app.prompt.cursor as u16;
+                frame.set_cursor_position((cursor_x.min(layout[1].right().saturating_sub(2)), layout[1].y + 1));
+            }
+
+        }
+        Mode::Config => {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![
+                    Constraint::Min(3),
+                    Constraint::Max(3),
+                ])
+                .split(frame.area());
+
+            let theme_color = color(theme.primary);
+            let editor = &app.config_editor;
+            let label_width = 24;
+
+            let list_items: Vec<ListItem> = editor
+                .rows()
+                .into_iter()
+                .enumerate()
+                .map(|(index, row)| {
+                    let (label, value) = editor.row_text(row);
+                    match row {
+                        Row::Header(_) => ListItem::new(Line::from(Span::styled(
+                            label,
+                            Style::default().fg(theme_color).add_modifier(Modifier::BOLD),
+                        ))),
+                        _ => {
+                            let (mut label, mut value) = (label, value);
+                            // Show the text being typed in place of the field it edits.
+                            if let (Some(input), true) = (&editor.input, index == editor.selected) {
+                                match row {
+                                    Row::Binding(..) => label = format!("{}_", input),
+                                    _ => value = format!("{}_", input),
+                                }
+                            }
+                            ListItem::new(Line::from(format!("  {:<label_width$}{}", label, value)))
+                        }
+                    }
+                })
+                .collect();
+
+            let title = if editor.dirty { "[Config *]" } else { "[Config]" };
+            let list = List::new(list_items)
+                .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title).title_alignment(Alignment::Center).border_style(Style::default().fg(theme_color)))
+                .style(Style::default().fg(theme_color))
+                .highlight_style(Style::default().fg(color(theme.background)).bg(theme_color))
+                .highlight_symbol("/");
+
+            let mut list_state = ListState::default().with_selected(Some(editor.selected));
+            frame.render_stateful_widget(list, layout[0], &mut list_state);
+
+            let (footer, footer_color) = match &editor.status {
+                Some(Status::Error(message)) => (message.clone(), color(theme.error)),
+                Some(Status::Info(message)) => (message.clone(), theme_color),
+                None if editor.input.is_some() => ("enter: apply  esc: cancel".to_string(), theme_color),
+                None => {
+                    let keys = &app.keymap;
+                    let help = [
+                        (TuiCommand::Select, "edit"),
+                        (TuiCommand::MoveRight, "change"),
+                        (TuiCommand::AddItem, "add bind"),
+                        (TuiCommand::RemoveItem, "remove bind"),
+                        (TuiCommand::Save, "save"),
+                        (TuiCommand::Revert, "revert"),
+                        (TuiCommand::Back, "menu"),
+                    ]
+                    .iter()
+                    .map(|(command, label)| format!("{}: {}", keys.chords(*command), label))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                    (help, theme_color)
+                }
+            };
+
+            let help = Paragraph::new(footer).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(theme_color))).style(Style::default().fg(footer_color));
+
+            frame.render_widget(help, layout[1]);
         }
         _ => {}
     }
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// Opaque, platform-neutral handle to a top-level window.
+///
+/// On Windows this wraps the raw `HWND`; the mock backend hands out sequential ids.
+/// Handles are plain integers, so they can be moved freely between threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowHandle(pub u64);
+
+/// Client area of a window in screen coordinates, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Access to the windows of the host desktop.
+pub trait WindowBackend: Debug + Send + Sync {
+    /// Enumerates all top-level windows.
+    fn windows(&self) -> Vec<WindowHandle>;
+
+    /// Returns the title of a window, or `None` if it has none or no longer exists.
+    fn title(&self, handle: WindowHandle) -> Option<String>;
+
+    /// Returns the client area of a window in screen coordinates.
+    fn geometry(&self, handle: WindowHandle) -> Option<WindowRect>;
+
+    /// Checks if a window is currently shown on screen.
+    fn is_visible(&self, handle: WindowHandle) -> bool;
+
+    /// Checks if a window still exists.
+    fn is_alive(&self, handle: WindowHandle) -> bool;
+
+    /// Returns every visible window that has a title, together with that title.
+    fn visible_windows(&self) -> Vec<(WindowHandle, String)> {
+        self.windows()
+            .into_iter()
+            .filter(|handle| self.is_visible(*handle))
+            .filter_map(|handle| {
+                self.title(handle)
+                    .filter(|title| !title.is_empty())
+                    .map(|title| (handle, title))
+            })
+            .collect()
+    }
+}
+
+/// Returns the window backend for the current platform.
+///
+/// Outside of Windows this is a [`MockBackend`] seeded with one window per
+/// comma-separated title in `AIM2GO_MOCK_WINDOWS`, so attach can be exercised on Linux.
+pub fn native_backend() -> Arc<dyn WindowBackend> {
+    #[cfg(windows)]
+    {
+        Arc::new(win32::Win32Backend::new())
+    }
+
+    #[cfg(not(windows))]
+    {
+        let backend = MockBackend::new();
+        if let Ok(titles) = std::env::var("AIM2GO_MOCK_WINDOWS") {
+            for title in titles.split(',').map(str::trim).filter(|title| !title.is_empty()) {
+                backend.add_window(title, WindowRect { x: 0, y: 0, width: 1920, height: 1080 });
+            }
+        }
+        Arc::new(backend)
+    }
+}
+
+/// State of a window owned by the [`MockBackend`].
+#[derive(Debug, Clone)]
+pub struct MockWindow {
+    pub title: String,
+    pub rect: WindowRect,
+    pub visible: bool,
+    pub alive: bool,
+}
+
+/// In-memory window backend for tests and non-Windows builds.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    windows: Mutex<Vec<MockWindow>>,
+}
+
+impl MockBackend {
+    /// Constructs an empty [`MockBackend`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a visible window and returns its handle.
+    pub fn add_window(&self, title: &str, rect: WindowRect) -> WindowHandle {
+        let mut windows = self.windows.lock().unwrap();
+        windows.push(MockWindow {
+            title: title.to_string(),
+            rect,
+            visible: true,
+            alive: true,
+        });
+        WindowHandle(windows.len() as u64)
+    }
+
+    /// Shows or hides a window.
+    pub fn set_visible(&self, handle: WindowHandle, visible: bool) {
+        self.with_window(handle, |window| window.visible = visible);
+    }
+
+    /// Moves or resizes a window.
+    pub fn set_rect(&self, handle: WindowHandle, rect: WindowRect) {
+        self.with_window(handle, |window| window.rect = rect);
+    }
+
+    /// Destroys a window; its handle stays allocated but is no longer alive.
+    pub fn close(&self, handle: WindowHandle) {
+        self.with_window(handle, |window| {
+            window.alive = false;
+            window.visible = false;
+        });
+    }
+
+    fn with_window<T>(&self, handle: WindowHandle, f: impl FnOnce(&mut MockWindow) -> T) -> Option<T> {
+        let mut windows = self.windows.lock().unwrap();
+        let index = usize::try_from(handle.0).ok()?.checked_sub(1)?;
+        windows.get_mut(index).map(f)
+    }
+
+    fn alive_window<T>(&self, handle: WindowHandle, f: impl FnOnce(&MockWindow) -> T) -> Option<T> {
+        self.with_window(handle, |window| window.alive.then(|| f(window))).flatten()
+    }
+}
+
+impl WindowBackend for MockBackend {
+    fn windows(&self) -> Vec<WindowHandle> {
+        let windows = self.windows.lock().unwrap();
+        (1..=windows.len() as u64)
+            .map(WindowHandle)
+            .filter(|handle| windows[(handle.0 - 1) as usize].alive)
+            .collect()
+    }
+
+    fn title(&self, handle: WindowHandle) -> Option<String> {
+        self.alive_window(handle, |window| window.title.clone())
+    }
+
+    fn geometry(&self, handle: WindowHandle) -> Option<WindowRect> {
+        self.alive_window(handle, |window| window.rect)
+    }
+
+    fn is_visible(&self, handle: WindowHandle) -> bool {
+        self.alive_window(handle, |window| window.visible).unwrap_or(false)
+    }
+
+    fn is_alive(&self, handle: WindowHandle) -> bool {
+        self.alive_window(handle, |_| ()).is_some()
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use super::{WindowBackend, WindowHandle, WindowRect};
+    use winapi::shared::windef::{DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, HWND, POINT, RECT};
+    use winapi::um::winuser::{
+        ClientToScreen, EnumWindows, GetClientRect, GetWindowTextLengthW, GetWindowTextW, IsIconic,
+        IsWindow, IsWindowVisible, SetProcessDpiAwarenessContext,
+    };
+
+    /// Window backend built on the Win32 API.
+    #[derive(Debug)]
+    pub struct Win32Backend;
+
+    impl Win32Backend {
+        /// Creates the backend, making the process DPI aware so Windows reports
+        /// geometry in physical pixels instead of scaling it to 96 DPI.
+        pub fn new() -> Self {
+            // Fails if the awareness was set already, which is fine.
+            unsafe {
+                SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+            }
+            Win32Backend
+        }
+    }
+
+    fn hwnd(handle: WindowHandle) -> HWND {
+        handle.0 as usize as HWND
+    }
+
+    impl WindowBackend for Win32Backend {
+        fn windows(&self) -> Vec<WindowHandle> {
+            let mut handles: Vec<WindowHandle> = Vec::new();
+
+            // Callback to collect window handles
+            unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: isize) -> i32 {
+                let handles = &mut *(lparam as *mut Vec<WindowHandle>);
+                handles.push(WindowHandle(hwnd as usize as u64));
+                1 // Continue enumeration
+            }
+
+            unsafe {
+                EnumWindows(Some(enum_windows_callback), &mut handles as *mut _ as isize);
+            }
+
+            handles
+        }
+
+        fn title(&self, handle: WindowHandle) -> Option<String> {
+            let hwnd = hwnd(handle);
+            unsafe {
+                let length = GetWindowTextLengthW(hwnd) as usize;
+                if length == 0 {
+                    return None;
+                }
+                let mut buffer = vec![0u16; length + 1];
+                GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+                Some(String::from_utf16_lossy(&buffer[..length]))
+            }
+        }
+
+        fn geometry(&self, handle: WindowHandle) -> Option<WindowRect> {
+            let hwnd = hwnd(handle);
+            unsafe {
+                let mut rect: RECT = std::mem::zeroed();
+                if GetClientRect(hwnd, &mut rect) == 0 {
+                    return None;
+                }
+                let mut origin = POINT { x: rect.left, y: rect.top };
+                if ClientToScreen(hwnd, &mut origin) == 0 {
+                    return None;
+                }
+                Some(WindowRect {
+                    x: origin.x,
+                    y: origin.y,
+                    width: (rect.right - rect.left).max(0) as u32,
+                    height: (rect.bottom - rect.top).max(0) as u32,
+                })
+            }
+        }
+
+        fn is_visible(&self, handle: WindowHandle) -> bool {
+            let hwnd = hwnd(handle);
+            unsafe { IsWindowVisible(hwnd) != 0 && IsIconic(hwnd) == 0 }
+        }
+
+        fn is_alive(&self, handle: WindowHandle) -> bool {
+            unsafe { IsWindow(hwnd(handle)) != 0 }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable that overrides the default workspace root.
+pub const HOME_ENV: &str = "AIM2GO_HOME";
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Resolves the workspace root that holds all game directories.
+///
+/// In order of precedence: the `--root` flag, `$AIM2GO_HOME`, then `aim2go/` in the
+/// platform data dir (`$XDG_DATA_HOME`, `~/.local/share`, or `%APPDATA%`).
+pub fn resolve_root(flag: Option<&Path>) -> PathBuf {
+    resolve_root_with(flag, env::var_os(HOME_ENV))
+}
+
+/// Resolves the workspace root like [`resolve_root`], with `home` as the value of `$AIM2GO_HOME`.
+fn resolve_root_with(flag: Option<&Path>, home: Option<OsString>) -> PathBuf {
+    if let Some(root) = flag {
+        return root.to_path_buf();
+    }
+
+    if let Some(home) = home.filter(|home| !home.is_empty()) {
+        return PathBuf::from(home);
+    }
+
+    dirs::data_dir()
+        .map(|dir| dir.join("aim2go"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Sets the workspace root for the rest of the process.
+///
+/// Only the first call has an effect; call it before any game directory is touched.
+pub fn set_root(root: PathBuf) {
+    let _ = ROOT.set(root);
+}
+
+/// Returns the workspace root, resolving it without a flag if [`set_root`] was never called.
+pub fn root() -> &'static Path {
+    ROOT.get_or_init(|| resolve_root(None))
+}
+
+/// Returns the directory of a specific game: `<root>/<game>/`
+pub fn game_dir(game: &str) -> PathBuf {
+    root().join(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_flag_wins_over_the_environment() {
+        let root = resolve_root_with(Some(Path::new("/flag")), Some(OsString::from("/home")));
+        assert_eq!(root, Path::new("/flag"));
+    }
+
+    #[test]
+    fn the_environment_wins_over_the_data_dir() {
+        assert_eq!(resolve_root_with(None, Some(OsString::from("/home"))), Path::new("/home"));
+    }
+
+    #[test]
+    fn the_data_dir_is_the_fallback() {
+        let data_dir = dirs::data_dir().map(|dir| dir.join("aim2go")).unwrap_or_else(|| PathBuf::from("."));
+        assert_eq!(resolve_root_with(None, None), data_dir);
+        // An empty variable counts as unset.
+        assert_eq!(resolve_root_with(None, Some(OsString::new())), data_dir);
+    }
+}